@@ -1,6 +1,6 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Vec, Bytes, symbol_short, contracterror};
-use soroban_sdk::prng::Prng;
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Symbol, Vec, Bytes, symbol_short, contracterror};
+use soroban_sdk::xdr::ToXdr;
 
 #[contracterror]
 #[derive(Clone, Debug, Copy, Eq, PartialEq)]
@@ -18,10 +18,22 @@ pub enum AuthError {
     InvalidRecoveryKey = 10,
     MaxRecoveryKeysReached = 11,
     StorageError = 12,
+    SessionNotFound = 13,
+    SessionExpired = 14,
+    SessionExhausted = 15,
+    SessionOutOfScope = 16,
+    InvalidSession = 17,
+    AccountLocked = 18,
+    RateLimited = 19,
+    InvalidPolicy = 20,
+    DelegationNotFound = 21,
+    InvalidDelegation = 22,
+    MaxDelegatesReached = 23,
+    MaxSessionsReached = 24,
 }
 
 // Types of authentication challenges
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum ChallengeType {
     Login = 0,
@@ -38,8 +50,24 @@ pub enum DataKey {
     RecoveryKeyCount(Address), // User address -> count of recovery keys
     RecoveryKey(Address, u32), // User address, index -> recovery key
     Nonce(Address), // User address -> current nonce
+    RecoveryNonce(Address), // User address -> number of recoveries performed
+    Session(Address, BytesN<32>), // User address, session pubkey -> session key
+    SessionIndex(Address), // User address -> list of session pubkeys
+    Policy, // Rate limit and lockout policy
+    Attempts(Address), // User address -> challenge and failure counters
+    Delegation(Address, Address), // User address, delegate address -> delegation
+    Delegates(Address), // User address -> list of delegate addresses
+    Delegators(Address), // Delegate address -> list of users who delegated to it
+    SecurityLog(Address), // User address -> most recent security events
+    SecurityLogSeq(Address), // User address -> number of security events ever recorded
 }
 
+// Version of the `SecurityEvent` schema, bumped on any layout change
+pub const SECURITY_EVENT_VERSION: u32 = 2;
+
+// Number of security events kept on chain per user
+const SECURITY_LOG_LEN: u32 = 50;
+
 // User passkey data
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
@@ -49,6 +77,121 @@ pub struct Passkey {
     pub metadata: Bytes, // CBOR or JSON metadata about the passkey
 }
 
+// Contract calls a session key may authorize
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum SessionScope {
+    Contract(Address), // Any function on the contract
+    Function(Address, Symbol), // A single function on the contract
+}
+
+// Temporary ed25519 key issued by a passkey
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SessionKey {
+    pub user: Address,
+    pub pubkey: BytesN<32>,
+    pub expiry_ledger: u32, // Last ledger sequence the key is valid for
+    pub scopes: Vec<SessionScope>,
+    pub max_uses: u32,
+    pub uses: u32,
+}
+
+// Auth-relevant actions recorded in the security log
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum SecurityEventKind {
+    PasskeyAdded = 0,
+    PasskeyRevoked = 1, // Replaced by registration or recovery
+    ChallengeIssued = 2,
+    ChallengeVerified = 3,
+    ChallengeFailed = 4, // Superseded without being verified
+    AccountLocked = 5,
+    RecoveryKeyAdded = 6,
+    AccountRecovered = 7,
+    SessionIssued = 8,
+    SessionRevoked = 9,
+    DelegationGranted = 10,
+    DelegationRevoked = 11,
+}
+
+// What a security event is about, so audits can tell which key, delegate
+// or challenge was involved
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum SecuritySubject {
+    None,
+    Key(BytesN<32>), // Passkey, recovery or session public key
+    Account(Address), // Delegate address
+    Nonce(u64), // Challenge nonce
+}
+
+// Structured payload of every per-user auth event, published under the
+// topics `("security", user)` and kept in the user's rolling security log
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SecurityEvent {
+    pub version: u32,
+    pub seq: u64, // Per-user sequence number, starting at 1
+    pub user: Address,
+    pub kind: SecurityEventKind,
+    pub subject: SecuritySubject,
+    pub ledger: u32,
+    pub timestamp: u64,
+}
+
+// Roles a user can delegate limited authority to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum DelegateRole {
+    Caregiver = 0,
+    Clinician = 1,
+}
+
+// Actions a delegate may take on the user's behalf. Passkey and recovery
+// management are deliberately not delegable.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum DelegatePermission {
+    ViewData = 0,
+    ConfirmAlert = 1,
+}
+
+// Authority a user has delegated to a caregiver or clinician
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Delegation {
+    pub user: Address,
+    pub delegate: Address,
+    pub role: DelegateRole,
+    pub scope: Vec<DelegatePermission>,
+    pub expiry_ledger: u32, // Last ledger sequence the delegation is valid for
+    pub granted_at: u64,
+}
+
+// Owner-configurable rate limit and lockout policy, windows in ledgers
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AuthPolicy {
+    pub max_challenges_per_window: u32,
+    pub window_ledgers: u32,
+    pub max_failed_attempts: u32, // Consecutive failures before the first lockout
+    pub base_lockout_ledgers: u32, // Doubles with each further failure
+    pub max_lockout_ledgers: u32,
+}
+
+// Per-user challenge and failure counters
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AttemptState {
+    pub window_start: u32,
+    pub challenges_in_window: u32,
+    pub failed_attempts: u32,
+    pub locked_until: u32, // Ledger sequence the lockout ends at
+    pub pending_challenge: bool, // Latest challenge has not been verified yet
+    pub challenge_type: ChallengeType, // Type of the latest challenge
+}
+
 #[contract]
 pub struct AuthContract;
 
@@ -80,32 +223,33 @@ impl AuthContract {
         // User must authenticate
         user.require_auth();
         
+        if let Some(old) = env.storage().persistent().get::<DataKey, Passkey>(&DataKey::Passkey(user.clone())) {
+            Self::record_event(&env, &user, SecurityEventKind::PasskeyRevoked, SecuritySubject::Key(old.pubkey));
+        }
+        
         // Create passkey
         let passkey = Passkey {
             user: user.clone(),
-            pubkey,
+            pubkey: pubkey.clone(),
             metadata,
         };
         
         // Store the passkey
-        env.storage().instance().set(&DataKey::Passkey(user.clone()), &passkey);
+        env.storage().persistent().set(&DataKey::Passkey(user.clone()), &passkey);
         
         // Initialize nonce
-        env.storage().instance().set(&DataKey::Nonce(user.clone()), &0u64);
+        env.storage().persistent().set(&DataKey::Nonce(user.clone()), &0u64);
         
         // Initialize recovery key count
-        env.storage().instance().set(&DataKey::RecoveryKeyCount(user.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::RecoveryKeyCount(user.clone()), &0u32);
         
-        env.events().publish(
-            (symbol_short!("register"), symbol_short!("passkey")), 
-            user.clone()
-        );
+        Self::record_event(&env, &user, SecurityEventKind::PasskeyAdded, SecuritySubject::Key(pubkey));
         
         Ok(())
     }
     
     pub fn get_passkey(env: Env, user: Address) -> Result<Passkey, AuthError> {
-        let passkey = env.storage().instance().get::<DataKey, Passkey>(&DataKey::Passkey(user));
+        let passkey = env.storage().persistent().get::<DataKey, Passkey>(&DataKey::Passkey(user));
         if let Some(pk) = passkey {
             Ok(pk)
         } else {
@@ -113,35 +257,170 @@ impl AuthContract {
         }
     }
     
+    // Get the current challenge nonce for a user
+    pub fn get_nonce(env: Env, user: Address) -> Result<u64, AuthError> {
+        match env.storage().persistent().get::<DataKey, u64>(&DataKey::Nonce(user)) {
+            Some(nonce) => Ok(nonce),
+            None => Err(AuthError::NonceNotFound),
+        }
+    }
+    
     // Generate a challenge for authentication
-    // Returns a nonce to be signed by the user
+    // Returns a nonce to be signed by the user. A previous challenge that was
+    // never verified counts as a failed attempt, since an invalid signature
+    // aborts `verify_challenge` before anything can be recorded.
     pub fn generate_challenge(
         env: Env,
         user: Address,
         challenge_type: ChallengeType
     ) -> Result<u64, AuthError> {
+        // Only the user may start a challenge, so nobody can bump their nonce or lock them out
+        user.require_auth();
+        
         // Check if user exists
-        if !env.storage().instance().has(&DataKey::Passkey(user.clone())) {
+        if !env.storage().persistent().has(&DataKey::Passkey(user.clone())) {
             return Err(AuthError::PasskeyNotFound);
         }
         
+        let policy = Self::get_auth_policy(env.clone());
+        let ledger = env.ledger().sequence();
+        let mut attempts = Self::get_attempt_state(env.clone(), user.clone());
+        
+        if ledger < attempts.locked_until {
+            return Err(AuthError::AccountLocked);
+        }
+        
+        // Rate limit challenges per ledger window
+        if ledger >= attempts.window_start.saturating_add(policy.window_ledgers) {
+            attempts.window_start = ledger;
+            attempts.challenges_in_window = 0;
+        }
+        if attempts.challenges_in_window >= policy.max_challenges_per_window {
+            return Err(AuthError::RateLimited);
+        }
+        attempts.challenges_in_window += 1;
+        
         // Get current nonce
-        let current_nonce = match env.storage().instance().get::<DataKey, u64>(&DataKey::Nonce(user.clone())) {
+        let current_nonce = match env.storage().persistent().get::<DataKey, u64>(&DataKey::Nonce(user.clone())) {
             Some(nonce) => nonce,
             None => return Err(AuthError::NonceNotFound),
         };
         
+        // Settle the previous challenge as failed and lock out exponentially.
+        // The challenge issued alongside a lockout can't be answered, so it
+        // isn't left pending to be charged again once the lockout ends.
+        let mut locked = false;
+        if attempts.pending_challenge {
+            Self::record_event(&env, &user, SecurityEventKind::ChallengeFailed, SecuritySubject::Nonce(current_nonce));
+            attempts.failed_attempts += 1;
+            if attempts.failed_attempts >= policy.max_failed_attempts {
+                let doublings = attempts.failed_attempts - policy.max_failed_attempts;
+                let lockout = policy.base_lockout_ledgers
+                    .checked_shl(doublings)
+                    .filter(|lockout| *lockout >= policy.base_lockout_ledgers)
+                    .unwrap_or(policy.max_lockout_ledgers)
+                    .min(policy.max_lockout_ledgers);
+                attempts.locked_until = ledger.saturating_add(lockout);
+                locked = true;
+                
+                Self::record_event(&env, &user, SecurityEventKind::AccountLocked, SecuritySubject::None);
+            }
+        }
+        attempts.pending_challenge = !locked;
+        attempts.challenge_type = challenge_type;
+        env.storage().persistent().set(&DataKey::Attempts(user.clone()), &attempts);
+        
         // Increment nonce
         let new_nonce = current_nonce + 1;
-        env.storage().instance().set(&DataKey::Nonce(user.clone()), &new_nonce);
+        env.storage().persistent().set(&DataKey::Nonce(user.clone()), &new_nonce);
+        
+        Self::record_event(&env, &user, SecurityEventKind::ChallengeIssued, SecuritySubject::Nonce(new_nonce));
+        
+        Ok(new_nonce)
+    }
+    
+    // Message the passkey signs to answer a challenge, bound to the type of
+    // the user's latest challenge
+    pub fn challenge_message(env: Env, user: Address, nonce: u64) -> Bytes {
+        let challenge_type = Self::get_attempt_state(env.clone(), user.clone()).challenge_type;
+        (env.current_contract_address(), user, challenge_type, nonce).to_xdr(&env)
+    }
+    
+    // Verify a passkey signature over the latest challenge and clear failures
+    pub fn verify_challenge(
+        env: Env,
+        user: Address,
+        nonce: u64,
+        signature: BytesN<64>
+    ) -> Result<(), AuthError> {
+        let mut attempts = Self::get_attempt_state(env.clone(), user.clone());
+        if env.ledger().sequence() < attempts.locked_until {
+            return Err(AuthError::AccountLocked);
+        }
+        
+        let passkey = Self::get_passkey(env.clone(), user.clone())?;
+        let current_nonce = Self::get_nonce(env.clone(), user.clone())?;
+        if nonce != current_nonce || !attempts.pending_challenge {
+            return Err(AuthError::NonceUsed);
+        }
+        
+        // Traps on an invalid signature; the challenge then stays pending
+        let message = Self::challenge_message(env.clone(), user.clone(), nonce);
+        env.crypto().ed25519_verify(&passkey.pubkey, &message, &signature);
+        
+        attempts.pending_challenge = false;
+        attempts.failed_attempts = 0;
+        env.storage().persistent().set(&DataKey::Attempts(user.clone()), &attempts);
+        
+        Self::record_event(&env, &user, SecurityEventKind::ChallengeVerified, SecuritySubject::Nonce(nonce));
+        
+        Ok(())
+    }
+    
+    // Set the rate limit and lockout policy (owner only)
+    pub fn set_auth_policy(env: Env, policy: AuthPolicy) -> Result<(), AuthError> {
+        let owner = Self::get_owner_internal(&env)?;
+        owner.require_auth();
+        
+        if policy.max_challenges_per_window == 0
+            || policy.window_ledgers == 0
+            || policy.max_failed_attempts == 0
+            || policy.base_lockout_ledgers > policy.max_lockout_ledgers
+        {
+            return Err(AuthError::InvalidPolicy);
+        }
+        
+        env.storage().instance().set(&DataKey::Policy, &policy);
         
-        // Publish challenge event
         env.events().publish(
-            (symbol_short!("challenge"), symbol_short!("gen")),
-            (user.clone(), challenge_type, new_nonce)
+            (symbol_short!("policy"), symbol_short!("set")),
+            policy
         );
         
-        Ok(new_nonce)
+        Ok(())
+    }
+    
+    // Get the rate limit and lockout policy
+    pub fn get_auth_policy(env: Env) -> AuthPolicy {
+        env.storage().instance().get::<DataKey, AuthPolicy>(&DataKey::Policy).unwrap_or(AuthPolicy {
+            max_challenges_per_window: 5,
+            window_ledgers: 60, // About five minutes
+            max_failed_attempts: 3,
+            base_lockout_ledgers: 60,
+            max_lockout_ledgers: 17_280, // About a day
+        })
+    }
+    
+    // Get a user's challenge and failure counters
+    pub fn get_attempt_state(env: Env, user: Address) -> AttemptState {
+        env.storage().persistent().get::<DataKey, AttemptState>(&DataKey::Attempts(user)).unwrap_or(AttemptState {
+            window_start: 0,
+            challenges_in_window: 0,
+            failed_attempts: 0,
+            locked_until: 0,
+            pending_challenge: false,
+            challenge_type: ChallengeType::Login,
+        })
     }
     
     // Add a recovery key to a user's account
@@ -154,10 +433,7 @@ impl AuthContract {
         user.require_auth();
         
         // Get current recovery key count
-        let count = match env.storage().instance().get::<DataKey, u32>(&DataKey::RecoveryKeyCount(user.clone())) {
-            Some(count) => count,
-            None => 0,
-        };
+        let count = env.storage().persistent().get::<DataKey, u32>(&DataKey::RecoveryKeyCount(user.clone())).unwrap_or_default();
         
         // Maximum of 3 recovery keys
         if count >= 3 {
@@ -165,42 +441,46 @@ impl AuthContract {
         }
         
         // Store the new recovery key
-        env.storage().instance().set(&DataKey::RecoveryKey(user.clone(), count), &recovery_key_pk);
+        env.storage().persistent().set(&DataKey::RecoveryKey(user.clone(), count), &recovery_key_pk);
         
         // Increment count
-        env.storage().instance().set(&DataKey::RecoveryKeyCount(user.clone()), &(count + 1));
+        env.storage().persistent().set(&DataKey::RecoveryKeyCount(user.clone()), &(count + 1));
         
-        env.events().publish(
-            (symbol_short!("recovery"), symbol_short!("add")),
-            (user.clone(), count)
-        );
+        Self::record_event(&env, &user, SecurityEventKind::RecoveryKeyAdded, SecuritySubject::Key(recovery_key_pk));
         
         Ok(count)
     }
     
-    // Recover account by replacing the main passkey
+    // Message a recovery key signs to replace the passkey. It is bound to the
+    // user's recovery nonce, which advances on every recovery, and to the new
+    // passkey, so a signature can be neither replayed nor redirected.
+    pub fn recovery_message(env: Env, user: Address, new_pubkey: BytesN<32>) -> Bytes {
+        let nonce = env.storage().persistent().get::<DataKey, u64>(&DataKey::RecoveryNonce(user.clone())).unwrap_or_default();
+        (env.current_contract_address(), user, ChallengeType::Recovery, nonce, new_pubkey).to_xdr(&env)
+    }
+    
+    // Recover account by replacing the main passkey, authorized by a signature
+    // from one of the user's recovery keys over `recovery_message`
     pub fn recover_account(
         env: Env,
         user: Address,
         recovery_key_pk_to_check: BytesN<32>,
         new_pubkey: BytesN<32>,
-        metadata: Bytes
+        metadata: Bytes,
+        signature: BytesN<64>
     ) -> Result<(), AuthError> {
         // Check if user exists
-        if !env.storage().instance().has(&DataKey::Passkey(user.clone())) {
+        if !env.storage().persistent().has(&DataKey::Passkey(user.clone())) {
             return Err(AuthError::PasskeyNotFound);
         }
         
         // Get count of recovery keys
-        let count = match env.storage().instance().get::<DataKey, u32>(&DataKey::RecoveryKeyCount(user.clone())) {
-            Some(count) => count,
-            None => 0,
-        };
+        let count = env.storage().persistent().get::<DataKey, u32>(&DataKey::RecoveryKeyCount(user.clone())).unwrap_or_default();
         
         // Check if provided recovery key matches any stored recovery key
         let mut key_found = false;
         for i in 0..count {
-            if let Some(key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::RecoveryKey(user.clone(), i)) {
+            if let Some(key) = env.storage().persistent().get::<DataKey, BytesN<32>>(&DataKey::RecoveryKey(user.clone(), i)) {
                 if key == recovery_key_pk_to_check {
                     key_found = true;
                     break;
@@ -212,27 +492,389 @@ impl AuthContract {
             return Err(AuthError::InvalidRecoveryKey);
         }
         
+        // Traps on an invalid signature
+        let message = Self::recovery_message(env.clone(), user.clone(), new_pubkey.clone());
+        env.crypto().ed25519_verify(&recovery_key_pk_to_check, &message, &signature);
+        
+        // Spend the recovery nonce so the signature can't be used again
+        let recovery_nonce = env.storage().persistent().get::<DataKey, u64>(&DataKey::RecoveryNonce(user.clone())).unwrap_or_default();
+        env.storage().persistent().set(&DataKey::RecoveryNonce(user.clone()), &(recovery_nonce + 1));
+        
+        let old = Self::get_passkey(env.clone(), user.clone())?;
+        
         // Create new passkey
         let passkey = Passkey {
             user: user.clone(),
-            pubkey: new_pubkey,
+            pubkey: new_pubkey.clone(),
             metadata,
         };
         
         // Store the new passkey
-        env.storage().instance().set(&DataKey::Passkey(user.clone()), &passkey);
+        env.storage().persistent().set(&DataKey::Passkey(user.clone()), &passkey);
         
         // Reset nonce
-        env.storage().instance().set(&DataKey::Nonce(user.clone()), &0u64);
+        env.storage().persistent().set(&DataKey::Nonce(user.clone()), &0u64);
         
-        env.events().publish(
-            (symbol_short!("recovery"), symbol_short!("done")),
-            user.clone()
-        );
+        Self::record_event(&env, &user, SecurityEventKind::PasskeyRevoked, SecuritySubject::Key(old.pubkey));
+        Self::record_event(&env, &user, SecurityEventKind::AccountRecovered, SecuritySubject::Key(new_pubkey));
+        
+        Ok(())
+    }
+    
+    // List the recovery public keys registered for a user
+    pub fn get_recovery_keys(env: Env, user: Address) -> Result<Vec<BytesN<32>>, AuthError> {
+        let count = env.storage().persistent().get::<DataKey, u32>(&DataKey::RecoveryKeyCount(user.clone())).unwrap_or_default();
+        
+        let mut keys = Vec::new(&env);
+        for i in 0..count {
+            if let Some(key) = env.storage().persistent().get::<DataKey, BytesN<32>>(&DataKey::RecoveryKey(user.clone(), i)) {
+                keys.push_back(key);
+            }
+        }
+        
+        Ok(keys)
+    }
+    
+    // Issue a session key scoped to specific contract calls
+    pub fn issue_session(
+        env: Env,
+        user: Address,
+        session_pubkey: BytesN<32>,
+        expiry_ledger: u32,
+        scopes: Vec<SessionScope>,
+        max_uses: u32
+    ) -> Result<(), AuthError> {
+        // Only the passkey can issue sessions
+        user.require_auth();
+        
+        if !env.storage().persistent().has(&DataKey::Passkey(user.clone())) {
+            return Err(AuthError::PasskeyNotFound);
+        }
+        
+        if expiry_ledger <= env.ledger().sequence() || max_uses == 0 || scopes.is_empty() {
+            return Err(AuthError::InvalidSession);
+        }
+        
+        // A session must never be able to manage passkeys, recovery or other sessions
+        let this_contract = env.current_contract_address();
+        for scope in scopes.iter() {
+            let target = match scope {
+                SessionScope::Contract(target) => target,
+                SessionScope::Function(target, _) => target,
+            };
+            if target == this_contract {
+                return Err(AuthError::InvalidSession);
+            }
+        }
+        
+        let session = SessionKey {
+            user: user.clone(),
+            pubkey: session_pubkey.clone(),
+            expiry_ledger,
+            scopes,
+            max_uses,
+            uses: 0,
+        };
+        
+        let key = DataKey::Session(user.clone(), session_pubkey.clone());
+        if !env.storage().persistent().has(&key) {
+            let mut index = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&DataKey::SessionIndex(user.clone())).unwrap_or(Vec::new(&env));
+            // Maximum of 10 sessions until expired ones are revoked
+            if index.len() >= 10 {
+                return Err(AuthError::MaxSessionsReached);
+            }
+            index.push_back(session_pubkey.clone());
+            env.storage().persistent().set(&DataKey::SessionIndex(user.clone()), &index);
+        }
+        env.storage().persistent().set(&key, &session);
+        
+        Self::record_event(&env, &user, SecurityEventKind::SessionIssued, SecuritySubject::Key(session_pubkey));
+        
+        Ok(())
+    }
+    
+    // Revoke a session key before it expires
+    pub fn revoke_session(
+        env: Env,
+        user: Address,
+        session_pubkey: BytesN<32>
+    ) -> Result<(), AuthError> {
+        user.require_auth();
+        
+        let key = DataKey::Session(user.clone(), session_pubkey.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(AuthError::SessionNotFound);
+        }
+        env.storage().persistent().remove(&key);
+        
+        let mut index = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&DataKey::SessionIndex(user.clone())).unwrap_or(Vec::new(&env));
+        if let Some(i) = index.first_index_of(&session_pubkey) {
+            index.remove(i);
+        }
+        env.storage().persistent().set(&DataKey::SessionIndex(user.clone()), &index);
+        
+        Self::record_event(&env, &user, SecurityEventKind::SessionRevoked, SecuritySubject::Key(session_pubkey));
+        
+        Ok(())
+    }
+    
+    // List a user's session keys, including expired or used up ones not yet revoked
+    pub fn list_sessions(env: Env, user: Address) -> Result<Vec<SessionKey>, AuthError> {
+        let index = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&DataKey::SessionIndex(user.clone())).unwrap_or(Vec::new(&env));
+        
+        let mut sessions = Vec::new(&env);
+        for pubkey in index.iter() {
+            if let Some(session) = env.storage().persistent().get::<DataKey, SessionKey>(&DataKey::Session(user.clone(), pubkey)) {
+                sessions.push_back(session);
+            }
+        }
+        
+        Ok(sessions)
+    }
+    
+    // Check that a session key may currently authorize a call, without using it
+    pub fn check_session(
+        env: Env,
+        user: Address,
+        session_pubkey: BytesN<32>,
+        contract: Address,
+        fn_name: Symbol
+    ) -> Result<SessionKey, AuthError> {
+        let session = env.storage().persistent().get::<DataKey, SessionKey>(&DataKey::Session(user, session_pubkey));
+        let session = match session {
+            Some(session) => session,
+            None => return Err(AuthError::SessionNotFound),
+        };
+        
+        if env.ledger().sequence() > session.expiry_ledger {
+            return Err(AuthError::SessionExpired);
+        }
+        
+        if session.uses >= session.max_uses {
+            return Err(AuthError::SessionExhausted);
+        }
+        
+        let in_scope = session.scopes.iter().any(|scope| match scope {
+            SessionScope::Contract(target) => target == contract,
+            SessionScope::Function(target, name) => target == contract && name == fn_name,
+        });
+        if !in_scope {
+            return Err(AuthError::SessionOutOfScope);
+        }
+        
+        Ok(session)
+    }
+    
+    // Record one use of a session key for a call; invoked by the user's account
+    // contract while it checks a session signature
+    pub fn consume_session(
+        env: Env,
+        user: Address,
+        session_pubkey: BytesN<32>,
+        contract: Address,
+        fn_name: Symbol
+    ) -> Result<(), AuthError> {
+        user.require_auth();
+        
+        let mut session = Self::check_session(env.clone(), user.clone(), session_pubkey.clone(), contract, fn_name)?;
+        session.uses += 1;
+        env.storage().persistent().set(&DataKey::Session(user, session_pubkey), &session);
+        
+        Ok(())
+    }
+    
+    // Delegate limited authority to a caregiver or clinician, replacing any
+    // existing delegation to the same address
+    pub fn grant_delegation(
+        env: Env,
+        user: Address,
+        delegate: Address,
+        role: DelegateRole,
+        scope: Vec<DelegatePermission>,
+        expiry_ledger: u32
+    ) -> Result<(), AuthError> {
+        user.require_auth();
+        
+        if delegate == user || scope.is_empty() || expiry_ledger <= env.ledger().sequence() {
+            return Err(AuthError::InvalidDelegation);
+        }
+        
+        let key = DataKey::Delegation(user.clone(), delegate.clone());
+        if !env.storage().persistent().has(&key) {
+            let mut delegates = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::Delegates(user.clone())).unwrap_or(Vec::new(&env));
+            // Maximum of 10 delegates
+            if delegates.len() >= 10 {
+                return Err(AuthError::MaxDelegatesReached);
+            }
+            delegates.push_back(delegate.clone());
+            env.storage().persistent().set(&DataKey::Delegates(user.clone()), &delegates);
+            
+            let mut delegators = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::Delegators(delegate.clone())).unwrap_or(Vec::new(&env));
+            delegators.push_back(user.clone());
+            env.storage().persistent().set(&DataKey::Delegators(delegate.clone()), &delegators);
+        }
+        
+        let delegation = Delegation {
+            user: user.clone(),
+            delegate: delegate.clone(),
+            role,
+            scope,
+            expiry_ledger,
+            granted_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &delegation);
+        
+        Self::record_event(&env, &user, SecurityEventKind::DelegationGranted, SecuritySubject::Account(delegate));
+        
+        Ok(())
+    }
+    
+    // Revoke a delegation
+    pub fn revoke_delegation(
+        env: Env,
+        user: Address,
+        delegate: Address
+    ) -> Result<(), AuthError> {
+        user.require_auth();
+        
+        if !env.storage().persistent().has(&DataKey::Delegation(user.clone(), delegate.clone())) {
+            return Err(AuthError::DelegationNotFound);
+        }
+        Self::remove_delegation(&env, user, delegate);
         
         Ok(())
     }
     
+    // Revoke every delegation a user has granted
+    pub fn revoke_all_delegations(env: Env, user: Address) -> Result<u32, AuthError> {
+        user.require_auth();
+        
+        let delegates = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::Delegates(user.clone())).unwrap_or(Vec::new(&env));
+        for delegate in delegates.iter() {
+            Self::remove_delegation(&env, user.clone(), delegate);
+        }
+        
+        Ok(delegates.len())
+    }
+    
+    fn remove_delegation(env: &Env, user: Address, delegate: Address) {
+        env.storage().persistent().remove(&DataKey::Delegation(user.clone(), delegate.clone()));
+        
+        let mut delegates = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::Delegates(user.clone())).unwrap_or(Vec::new(env));
+        if let Some(i) = delegates.first_index_of(&delegate) {
+            delegates.remove(i);
+        }
+        env.storage().persistent().set(&DataKey::Delegates(user.clone()), &delegates);
+        
+        let mut delegators = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::Delegators(delegate.clone())).unwrap_or(Vec::new(env));
+        if let Some(i) = delegators.first_index_of(&user) {
+            delegators.remove(i);
+        }
+        env.storage().persistent().set(&DataKey::Delegators(delegate.clone()), &delegators);
+        
+        Self::record_event(env, &user, SecurityEventKind::DelegationRevoked, SecuritySubject::Account(delegate));
+    }
+    
+    // List delegations a user has granted, including expired ones not yet revoked
+    pub fn list_delegations(env: Env, user: Address) -> Result<Vec<Delegation>, AuthError> {
+        let delegates = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::Delegates(user.clone())).unwrap_or(Vec::new(&env));
+        
+        let mut result = Vec::new(&env);
+        for delegate in delegates.iter() {
+            if let Some(delegation) = env.storage().persistent().get::<DataKey, Delegation>(&DataKey::Delegation(user.clone(), delegate)) {
+                result.push_back(delegation);
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    // List delegations granted to a caregiver or clinician
+    pub fn list_delegated_to(env: Env, delegate: Address) -> Result<Vec<Delegation>, AuthError> {
+        let delegators = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::Delegators(delegate.clone())).unwrap_or(Vec::new(&env));
+        
+        let mut result = Vec::new(&env);
+        for user in delegators.iter() {
+            if let Some(delegation) = env.storage().persistent().get::<DataKey, Delegation>(&DataKey::Delegation(user, delegate.clone())) {
+                result.push_back(delegation);
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    // Check whether an address currently holds a delegated role for a user
+    pub fn is_delegate_for(env: Env, user: Address, delegate: Address, role: DelegateRole) -> bool {
+        match Self::get_active_delegation(&env, user, delegate) {
+            Some(delegation) => delegation.role == role,
+            None => false,
+        }
+    }
+    
+    // Check whether an address may currently take an action for a user
+    pub fn has_delegated_permission(env: Env, user: Address, delegate: Address, permission: DelegatePermission) -> bool {
+        match Self::get_active_delegation(&env, user, delegate) {
+            Some(delegation) => delegation.scope.contains(permission),
+            None => false,
+        }
+    }
+    
+    fn get_active_delegation(env: &Env, user: Address, delegate: Address) -> Option<Delegation> {
+        let delegation = env.storage().persistent().get::<DataKey, Delegation>(&DataKey::Delegation(user, delegate))?;
+        if env.ledger().sequence() > delegation.expiry_ledger {
+            return None;
+        }
+        Some(delegation)
+    }
+    
+    // Page through a user's security log, newest first. A cursor of 0 starts
+    // from the latest event; otherwise only events older than `cursor` (a
+    // `seq` from a previous page) are returned.
+    pub fn get_security_log(env: Env, user: Address, cursor: u64, limit: u32) -> Vec<SecurityEvent> {
+        let log = env.storage().persistent().get::<DataKey, Vec<SecurityEvent>>(&DataKey::SecurityLog(user)).unwrap_or(Vec::new(&env));
+        let limit = limit.min(SECURITY_LOG_LEN);
+        
+        let mut page = Vec::new(&env);
+        for event in log.iter().rev() {
+            if page.len() >= limit {
+                break;
+            }
+            if cursor == 0 || event.seq < cursor {
+                page.push_back(event);
+            }
+        }
+        
+        page
+    }
+    
+    // Publish a structured security event and append it to the user's rolling log
+    fn record_event(env: &Env, user: &Address, kind: SecurityEventKind, subject: SecuritySubject) {
+        let seq = env.storage().persistent().get::<DataKey, u64>(&DataKey::SecurityLogSeq(user.clone())).unwrap_or_default() + 1;
+        env.storage().persistent().set(&DataKey::SecurityLogSeq(user.clone()), &seq);
+        
+        let event = SecurityEvent {
+            version: SECURITY_EVENT_VERSION,
+            seq,
+            user: user.clone(),
+            kind,
+            subject,
+            ledger: env.ledger().sequence(),
+            timestamp: env.ledger().timestamp(),
+        };
+        
+        let mut log = env.storage().persistent().get::<DataKey, Vec<SecurityEvent>>(&DataKey::SecurityLog(user.clone())).unwrap_or(Vec::new(env));
+        if log.len() >= SECURITY_LOG_LEN {
+            log.pop_front();
+        }
+        log.push_back(event.clone());
+        env.storage().persistent().set(&DataKey::SecurityLog(user.clone()), &log);
+        
+        env.events().publish(
+            (symbol_short!("security"), user.clone()),
+            event
+        );
+    }
+    
     // Get owner of the contract
     fn get_owner_internal(env: &Env) -> Result<Address, AuthError> {
         let owner = env.storage().instance().get::<DataKey, Address>(&DataKey::Owner);
@@ -247,15 +889,11 @@ impl AuthContract {
 /// Unit tests for the auth contract
 #[cfg(test)]
 mod test {
+    extern crate std;
+    
     use super::*;
-    use soroban_sdk::testutils::{Address as _, AuthorizedFunction, Events, MockAuth, MockAuthInvoke};
-    use soroban_sdk::{vec, BytesN, Env};
-    use soroban_sdk::prng::SorobanPrng;
-
-    // Helper to create Bytes for messages
-    fn create_message_bytes(env: &Env, content: &str) -> Bytes {
-        Bytes::from_slice(env, content.as_bytes())
-    }
+    use soroban_sdk::testutils::{Address as _, Events, Ledger};
+    use soroban_sdk::{vec, BytesN, Env, IntoVal};
 
     #[test]
     fn test_initialize() {
@@ -268,18 +906,21 @@ mod test {
         
         // Initialize the contract
         let client = AuthContractClient::new(&env, &contract_id);
-        assert!(client.initialize(&owner).is_ok());
+        client.initialize(&owner);
         
         // Verify event was published
         let events = env.events().all();
         assert_eq!(events.len(), 1);
         assert_eq!(
-            events[0],
-            (
-                contract_id.clone(),
-                (symbol_short!("init"), symbol_short!("auth")).into_val(&env),
-                owner.into_val(&env)
-            )
+            events,
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("init"), symbol_short!("auth")).into_val(&env),
+                    owner.into_val(&env)
+                )
+            ]
         );
     }
     
@@ -298,13 +939,13 @@ mod test {
         
         // Initialize the contract
         let client = AuthContractClient::new(&env, &contract_id);
-        client.initialize(&owner).unwrap();
+        client.initialize(&owner);
         
         // Register a passkey
-        client.register_passkey(&user, &public_key, &Bytes::new(&env)).unwrap();
+        client.register_passkey(&user, &public_key, &Bytes::new(&env));
         
         // Get the passkey
-        let passkey = client.get_passkey(&user).unwrap();
+        let passkey = client.get_passkey(&user);
         assert_eq!(passkey.pubkey, public_key);
     }
 
@@ -316,17 +957,15 @@ mod test {
         let client = AuthContractClient::new(&env, &contract_id);
 
         let owner = Address::generate(&env);
-        client.initialize(&owner).unwrap();
+        client.initialize(&owner);
         
         let user = Address::generate(&env);
-        client.register_passkey(&user, &BytesN::from_array(&env, &[0;32]), &Bytes::new(&env)).unwrap();
+        client.register_passkey(&user, &BytesN::from_array(&env, &[0;32]), &Bytes::new(&env));
 
         let challenge_type = ChallengeType::Login;
-        let result = client.generate_challenge(&user, &challenge_type);
-        assert!(result.is_ok());
-
-        let passkey_after = client.get_passkey(&user).unwrap();
-        assert!(passkey_after.last_used_timestamp > 0);
+        let nonce = client.generate_challenge(&user, &challenge_type);
+        assert_eq!(nonce, 1);
+        assert_eq!(client.get_nonce(&user), 1);
     }
 
     #[test]
@@ -337,46 +976,332 @@ mod test {
         let client = AuthContractClient::new(&env, &contract_id);
 
         let owner = Address::generate(&env);
-        client.initialize(&owner).unwrap();
+        client.initialize(&owner);
         let user = Address::generate(&env);
-        client.register_passkey(&user, &BytesN::from_array(&env, &[0;32]), &Bytes::new(&env)).unwrap();
+        client.register_passkey(&user, &BytesN::from_array(&env, &[0;32]), &Bytes::new(&env));
 
-        let result = client.add_recovery_key(&user, &BytesN::from_array(&env, &[1;32]));
-        assert!(result.is_ok());
+        let index = client.add_recovery_key(&user, &BytesN::from_array(&env, &[1;32]));
+        assert_eq!(index, 0);
 
-        let recovery_keys = client.get_recovery_keys(&user).unwrap();
+        let recovery_keys = client.get_recovery_keys(&user);
         assert_eq!(recovery_keys.len(), 1);
         assert_eq!(recovery_keys.get_unchecked(0), BytesN::from_array(&env, &[1;32]));
     }
 
     #[test]
     fn test_recover_account() {
+        use ed25519_dalek::{Signer, SigningKey};
+
         let env = Env::default();
         env.mock_all_auths();
         let contract_id = env.register_contract(None, AuthContract);
         let client = AuthContractClient::new(&env, &contract_id);
 
         let owner = Address::generate(&env);
-        client.initialize(&owner).unwrap();
+        client.initialize(&owner);
         let user = Address::generate(&env);
-        let prng = SorobanPrng::new_from_seed(&[2;32]);
-        let (pk_orig, _sk_orig) = prng.ed25519_keypair();
-        client.register_passkey(&user, &pk_orig, &Bytes::new(&env)).unwrap();
+        let pk_orig = BytesN::from_array(&env, &[2; 32]);
+        client.register_passkey(&user, &pk_orig, &Bytes::new(&env));
 
-        let (pk_rec, sk_rec) = prng.ed25519_keypair();
-        client.add_recovery_key(&user, &pk_rec).unwrap();
-        let recovery_keys = client.get_recovery_keys(&user).unwrap();
+        let recovery_key = SigningKey::from_bytes(&[3; 32]);
+        let pk_rec = BytesN::from_array(&env, &recovery_key.verifying_key().to_bytes());
+        client.add_recovery_key(&user, &pk_rec);
+        let recovery_keys = client.get_recovery_keys(&user);
         assert_eq!(recovery_keys.len(), 1);
         assert_eq!(recovery_keys.get_unchecked(0), pk_rec);
 
-        let (pk_new, _sk_new) = prng.ed25519_keypair();
-        let message_for_recovery = create_message_bytes(&env, "recovery message");
-        let recovery_sig = sk_rec.sign(&message_for_recovery).unwrap();
+        client.generate_challenge(&user, &ChallengeType::Login);
 
-        client.recover_account(&user, &pk_rec, &pk_new, &message_for_recovery, &Bytes::new(&env)).unwrap();
+        let pk_new = BytesN::from_array(&env, &[4; 32]);
+        let message: std::vec::Vec<u8> = client.recovery_message(&user, &pk_new).iter().collect();
+        let signature = BytesN::from_array(&env, &recovery_key.sign(&message).to_bytes());
 
-        let new_passkey_obj = client.get_passkey(&user).unwrap();
+        client.recover_account(&user, &pk_rec, &pk_new, &Bytes::new(&env), &signature);
+
+        let new_passkey_obj = client.get_passkey(&user);
         assert_eq!(new_passkey_obj.pubkey, pk_new);
-        assert_eq!(client.get_nonce(&user).unwrap(), 0); // Nonce should be reset
+        assert_eq!(client.get_nonce(&user), 0); // Nonce should be reset
+
+        // The recovery nonce is spent, so the signature no longer matches
+        let next_message: std::vec::Vec<u8> = client.recovery_message(&user, &pk_new).iter().collect();
+        assert_ne!(next_message, message);
+
+        // Only registered recovery keys are accepted
+        let res = client.try_recover_account(&user, &pk_new, &pk_new, &Bytes::new(&env), &signature);
+        assert_eq!(res, Err(Ok(AuthError::InvalidRecoveryKey)));
+    }
+
+    #[test]
+    fn test_session_scope_and_uses() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        let user = Address::generate(&env);
+        client.register_passkey(&user, &BytesN::from_array(&env, &[0; 32]), &Bytes::new(&env));
+
+        let data_contract = Address::generate(&env);
+        let session_pk = BytesN::from_array(&env, &[5; 32]);
+        let scopes = vec![&env, SessionScope::Function(data_contract.clone(), symbol_short!("store"))];
+        client.issue_session(&user, &session_pk, &(env.ledger().sequence() + 10), &scopes, &2);
+        assert_eq!(client.list_sessions(&user).len(), 1);
+
+        // Only the allowlisted function is in scope
+        let res = client.try_check_session(&user, &session_pk, &data_contract, &symbol_short!("delete"));
+        assert_eq!(res, Err(Ok(AuthError::SessionOutOfScope)));
+        let res = client.try_check_session(&user, &session_pk, &Address::generate(&env), &symbol_short!("store"));
+        assert_eq!(res, Err(Ok(AuthError::SessionOutOfScope)));
+
+        client.consume_session(&user, &session_pk, &data_contract, &symbol_short!("store"));
+        client.consume_session(&user, &session_pk, &data_contract, &symbol_short!("store"));
+        let res = client.try_consume_session(&user, &session_pk, &data_contract, &symbol_short!("store"));
+        assert_eq!(res, Err(Ok(AuthError::SessionExhausted)));
+        assert_eq!(client.list_sessions(&user).get_unchecked(0).uses, 2);
+    }
+
+    #[test]
+    fn test_session_expiry_and_revoke() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        let user = Address::generate(&env);
+        client.register_passkey(&user, &BytesN::from_array(&env, &[0; 32]), &Bytes::new(&env));
+
+        // Sessions may not target the auth contract itself
+        let session_pk = BytesN::from_array(&env, &[5; 32]);
+        let expiry = env.ledger().sequence() + 10;
+        let res = client.try_issue_session(&user, &session_pk, &expiry, &vec![&env, SessionScope::Contract(contract_id.clone())], &5);
+        assert_eq!(res, Err(Ok(AuthError::InvalidSession)));
+
+        let target = Address::generate(&env);
+        client.issue_session(&user, &session_pk, &expiry, &vec![&env, SessionScope::Contract(target.clone())], &5);
+        assert!(client.try_check_session(&user, &session_pk, &target, &symbol_short!("any")).is_ok());
+
+        env.ledger().with_mut(|li| li.sequence_number = expiry + 1);
+        let res = client.try_check_session(&user, &session_pk, &target, &symbol_short!("any"));
+        assert_eq!(res, Err(Ok(AuthError::SessionExpired)));
+
+        client.revoke_session(&user, &session_pk);
+        assert_eq!(client.list_sessions(&user).len(), 0);
+        let res = client.try_check_session(&user, &session_pk, &target, &symbol_short!("any"));
+        assert_eq!(res, Err(Ok(AuthError::SessionNotFound)));
+    }
+
+    fn set_test_policy(client: &AuthContractClient) {
+        client.set_auth_policy(&AuthPolicy {
+            max_challenges_per_window: 10,
+            window_ledgers: 100,
+            max_failed_attempts: 2,
+            base_lockout_ledgers: 10,
+            max_lockout_ledgers: 100,
+        });
+    }
+
+    #[test]
+    fn test_challenge_rate_limit() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        client.set_auth_policy(&AuthPolicy {
+            max_challenges_per_window: 2,
+            window_ledgers: 50,
+            max_failed_attempts: 100,
+            base_lockout_ledgers: 10,
+            max_lockout_ledgers: 100,
+        });
+        let user = Address::generate(&env);
+        client.register_passkey(&user, &BytesN::from_array(&env, &[0; 32]), &Bytes::new(&env));
+
+        client.generate_challenge(&user, &ChallengeType::Login);
+        client.generate_challenge(&user, &ChallengeType::Login);
+        let res = client.try_generate_challenge(&user, &ChallengeType::Login);
+        assert_eq!(res, Err(Ok(AuthError::RateLimited)));
+
+        env.ledger().with_mut(|li| li.sequence_number += 50);
+        assert_eq!(client.generate_challenge(&user, &ChallengeType::Login), 3);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_unverified_challenges_lock_out_exponentially() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        set_test_policy(&client);
+        let user = Address::generate(&env);
+        client.register_passkey(&user, &BytesN::from_array(&env, &[0; 32]), &Bytes::new(&env));
+
+        // Two abandoned challenges reach the failure threshold
+        client.generate_challenge(&user, &ChallengeType::Login);
+        client.generate_challenge(&user, &ChallengeType::Login);
+        client.generate_challenge(&user, &ChallengeType::Login);
+        let start = env.ledger().sequence();
+        assert_eq!(client.get_attempt_state(&user).locked_until, start + 10);
+        let res = client.try_generate_challenge(&user, &ChallengeType::Login);
+        assert_eq!(res, Err(Ok(AuthError::AccountLocked)));
+        let res = client.try_verify_challenge(&user, &3, &BytesN::from_array(&env, &[0; 64]));
+        assert_eq!(res, Err(Ok(AuthError::AccountLocked)));
+
+        // The next failure after the lockout doubles it
+        env.ledger().with_mut(|li| li.sequence_number = start + 10);
+        client.generate_challenge(&user, &ChallengeType::Login);
+        client.generate_challenge(&user, &ChallengeType::Login);
+        assert_eq!(client.get_attempt_state(&user).locked_until, start + 30);
+    }
+
+    #[test]
+    fn test_verify_challenge_resets_failures() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        set_test_policy(&client);
+        let user = Address::generate(&env);
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let pubkey = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+        client.register_passkey(&user, &pubkey, &Bytes::new(&env));
+
+        client.generate_challenge(&user, &ChallengeType::Login);
+        let nonce = client.generate_challenge(&user, &ChallengeType::Login);
+        assert_eq!(client.get_attempt_state(&user).failed_attempts, 1);
+
+        let message: std::vec::Vec<u8> = client.challenge_message(&user, &nonce).iter().collect();
+        let signature = BytesN::from_array(&env, &signing_key.sign(&message).to_bytes());
+        client.verify_challenge(&user, &nonce, &signature);
+
+        let attempts = client.get_attempt_state(&user);
+        assert_eq!(attempts.failed_attempts, 0);
+        assert!(!attempts.pending_challenge);
+
+        // A challenge can only be answered once
+        let res = client.try_verify_challenge(&user, &nonce, &signature);
+        assert_eq!(res, Err(Ok(AuthError::NonceUsed)));
+    }
+
+    #[test]
+    fn test_delegation_roles_and_scope() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        let user = Address::generate(&env);
+        let parent = Address::generate(&env);
+        let clinician = Address::generate(&env);
+        let expiry = env.ledger().sequence() + 100;
+
+        client.grant_delegation(&user, &parent, &DelegateRole::Caregiver, &vec![&env, DelegatePermission::ConfirmAlert], &expiry);
+        client.grant_delegation(&user, &clinician, &DelegateRole::Clinician, &vec![&env, DelegatePermission::ViewData], &expiry);
+
+        assert!(client.is_delegate_for(&user, &parent, &DelegateRole::Caregiver));
+        assert!(!client.is_delegate_for(&user, &parent, &DelegateRole::Clinician));
+        assert!(client.has_delegated_permission(&user, &clinician, &DelegatePermission::ViewData));
+        assert!(!client.has_delegated_permission(&user, &parent, &DelegatePermission::ViewData));
+        assert_eq!(client.list_delegations(&user).len(), 2);
+        assert_eq!(client.list_delegated_to(&clinician).get_unchecked(0).user, user);
+
+        // Delegations lapse at their expiry ledger
+        env.ledger().with_mut(|li| li.sequence_number = expiry + 1);
+        assert!(!client.is_delegate_for(&user, &parent, &DelegateRole::Caregiver));
+
+        let res = client.try_grant_delegation(&user, &user, &DelegateRole::Caregiver, &vec![&env, DelegatePermission::ViewData], &(expiry + 10));
+        assert_eq!(res, Err(Ok(AuthError::InvalidDelegation)));
+    }
+
+    #[test]
+    fn test_revoke_delegations() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        let user = Address::generate(&env);
+        let parent = Address::generate(&env);
+        let clinician = Address::generate(&env);
+        let expiry = env.ledger().sequence() + 100;
+        let scope = vec![&env, DelegatePermission::ViewData];
+
+        client.grant_delegation(&user, &parent, &DelegateRole::Caregiver, &scope, &expiry);
+        client.grant_delegation(&user, &clinician, &DelegateRole::Clinician, &scope, &expiry);
+
+        client.revoke_delegation(&user, &parent);
+        assert!(!client.is_delegate_for(&user, &parent, &DelegateRole::Caregiver));
+        assert_eq!(client.list_delegated_to(&parent).len(), 0);
+        let res = client.try_revoke_delegation(&user, &parent);
+        assert_eq!(res, Err(Ok(AuthError::DelegationNotFound)));
+
+        assert_eq!(client.revoke_all_delegations(&user), 1);
+        assert_eq!(client.list_delegations(&user).len(), 0);
+        assert!(!client.is_delegate_for(&user, &clinician, &DelegateRole::Clinician));
+    }
+
+    #[test]
+    fn test_security_log() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        let user = Address::generate(&env);
+        client.register_passkey(&user, &BytesN::from_array(&env, &[0; 32]), &Bytes::new(&env));
+        client.generate_challenge(&user, &ChallengeType::Login);
+        client.generate_challenge(&user, &ChallengeType::Transaction);
+
+        // Newest first: issued, failed (the superseded challenge), issued, passkey added
+        let log = client.get_security_log(&user, &0, &10);
+        assert_eq!(log.len(), 4);
+        assert_eq!(log.get_unchecked(0).kind, SecurityEventKind::ChallengeIssued);
+        assert_eq!(log.get_unchecked(1).kind, SecurityEventKind::ChallengeFailed);
+        assert_eq!(log.get_unchecked(3).kind, SecurityEventKind::PasskeyAdded);
+        assert_eq!(log.get_unchecked(3).version, SECURITY_EVENT_VERSION);
+
+        // Each entry names what it is about
+        assert_eq!(log.get_unchecked(0).subject, SecuritySubject::Nonce(2));
+        assert_eq!(log.get_unchecked(1).subject, SecuritySubject::Nonce(1));
+        assert_eq!(log.get_unchecked(3).subject, SecuritySubject::Key(BytesN::from_array(&env, &[0; 32])));
+
+        let page = client.get_security_log(&user, &log.get_unchecked(1).seq, &10);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page.get_unchecked(0).seq, 2);
+
+        // Every entry is also published as a structured event
+        let last = env.events().all().last().unwrap();
+        assert_eq!(last.1, (symbol_short!("security"), user.clone()).into_val(&env));
+    }
+
+    #[test]
+    fn test_security_log_is_bounded() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, AuthContract);
+        let client = AuthContractClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+        let user = Address::generate(&env);
+        for i in 0..(SECURITY_LOG_LEN + 5) {
+            client.register_passkey(&user, &BytesN::from_array(&env, &[i as u8; 32]), &Bytes::new(&env));
+        }
+
+        let log = client.get_security_log(&user, &0, &100);
+        assert_eq!(log.len(), SECURITY_LOG_LEN);
+        // First registration adds, every later one revokes then adds
+        assert_eq!(log.get_unchecked(0).seq, 2 * (SECURITY_LOG_LEN as u64 + 5) - 1);
+    }
+}
@@ -1,5 +1,6 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Symbol, Vec, String, Bytes, symbol_short, contracterror};
+use soroban_sdk::{contract, contractclient, contractimpl, contracttype, token, Address, BytesN, Env, Symbol, Vec, String, Bytes, symbol_short, contracterror};
+use soroban_sdk::xdr::ToXdr;
 
 #[contracterror]
 #[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
@@ -13,6 +14,33 @@ pub enum CommunityError {
     StorageError = 6,
     CommentNotFound = 7,
     NotModerator = 8,
+    CommentRemoved = 9,
+    AlreadyLiked = 10,
+    InvalidParentComment = 11,
+    NotAnonymous = 12,
+    AlreadyReported = 13,
+    ReportNotFound = 14,
+    AppealNotFound = 15,
+    AlreadyAppealed = 16,
+    InvalidThreshold = 17,
+    NotRemoved = 18,
+    NotRegistered = 19,
+    AccountTooNew = 20,
+    SelfVote = 21,
+    InvalidPolicy = 22,
+    CategoryNotFound = 23,
+    CategoryExists = 24,
+    CategoryArchived = 25,
+    InsufficientReputation = 26,
+    InvalidAmount = 27,
+    InsufficientRewards = 28,
+    RewardTokenNotSet = 29,
+    AlreadyFollowing = 30,
+    NotFollowing = 31,
+    TooManyMentions = 32,
+    NotExpert = 33,
+    NotAQuestion = 35,
+    IdentityMismatch = 36,
 }
 
 /// Data storage keys
@@ -21,18 +49,112 @@ pub enum CommunityError {
 pub enum DataKey {
     Owner,
     RegistryContract,
-    PostIndex,              // All post IDs
     Post(BytesN<32>),       // Post ID -> Post
-    Moderator(Address),     // Address -> bool
-    UserPosts(Address),     // User -> Vec<post_ids>
-    Category(Symbol),       // Category -> Vec<post_ids>
-    Vote(BytesN<32>, Address), // Post ID, User -> VoteType
+    Moderator(Address),     // Address -> ModeratorGrant
+    IndexLen(IndexKey),     // Number of IDs in an index
+    IndexPage(IndexKey, u32), // Index, page number -> up to INDEX_PAGE_SIZE IDs
+    QueuePos(IndexKey, BytesN<32>), // Queue index, post/comment ID -> position in the index
+    Vote(BytesN<32>, Address), // Post ID, User -> CastVote
     Reward(Address),        // User -> i128
     Comment(BytesN<32>),    // Comment data by ID
+    CommentLike(BytesN<32>, Address), // Comment ID, User -> bool
+    IdNonce,                // Counter mixed into generated IDs
+    AuthorKey(BytesN<32>),  // Anonymous post/comment ID -> author's ed25519 key
+    AuthorProofNonce(BytesN<32>), // Anonymous post/comment ID -> proofs accepted so far
+    IdentityCommitment(BytesN<32>), // Anonymous post/comment ID -> sha256(author XDR || salt)
+    IdentityEscrow(BytesN<32>), // Anonymous post/comment ID -> author and salt encrypted to the owner
+    AuthorKeyNonce(BytesN<32>), // Author key -> anonymous posts and comments it has signed
+    Report(BytesN<32>, Address), // Post/comment ID, Reporter -> ReportReason
+    ReportSummary(BytesN<32>), // Post/comment ID -> ReportSummary
+    ReportThreshold,        // Reports needed to auto-hide content
+    Appeal(BytesN<32>),     // Post/comment ID -> Appeal
+    Reputation(Address),    // User -> Reputation
+    VotePolicy,             // VotePolicy set by the owner
+    PostHistory(BytesN<32>), // Post ID -> Vec<PostRevision>, oldest first, at most POST_HISTORY_LEN
+    CategoryInfo(Symbol),   // Category -> CategoryInfo
+    CategoryList,           // All category symbols in creation order
+    RewardToken,            // Token contract paying out redeemed rewards
+    RewardRate(RewardAction), // Action -> reward points, overriding the default schedule
+    RewardHistory(Address), // User -> Vec<RewardEntry>, oldest first, bounded
+    CommentReward(BytesN<32>), // Comment ID -> i128 points paid for it, taken back on removal
+    CommentRewardDay(Address), // User -> (day, comments rewarded that day)
+    ModerationLog(BytesN<32>), // Post/comment ID -> Vec<ModerationEntry>, oldest first
+    Follow(Address, Address), // Follower, User -> FollowEntry
+    FollowLen(FollowIndex), // Number of addresses in a follow index
+    FollowPage(FollowIndex, u32), // Follow index, page number -> up to INDEX_PAGE_SIZE addresses
+    FollowedCategories(Address), // User -> Vec<Symbol> they follow
+    ExpertBadge(Address),   // User -> ExpertBadge
+}
+
+/// ID lists, stored in pages so no single entry grows without bound. Feed and
+/// comment indexes are append-only; the queues are compacted as items leave them.
+#[derive(Clone)]
+#[contracttype]
+pub enum IndexKey {
+    Approved,               // Posts in order of first approval
+    ApprovedInCategory(Symbol), // A category's posts in order of first approval
+    UserPosts(Address),     // A user's posts in creation order
+    Comments(BytesN<32>),   // A post's comments in creation order
+    ReportQueue(u32),       // Reported post/comment IDs with this many reports, capped at REPORT_BUCKETS
+    AppealQueue,            // Post/comment IDs with pending appeals
+}
+
+/// Follow lists, paged like `IndexKey` but compacted on unfollow
+#[derive(Clone)]
+#[contracttype]
+pub enum FollowIndex {
+    Following(Address),     // Users someone follows
+    Followers(Address),     // Users following someone
+}
+
+/// Where a follow sits in both follow indexes, so unfollowing can remove it in place
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FollowEntry {
+    pub following_pos: u32, // Position of the user in the follower's Following index
+    pub follower_pos: u32,  // Position of the follower in the user's Followers index
+    pub timestamp: u64,
+}
+
+/// IDs stored per index page
+const INDEX_PAGE_SIZE: u32 = 64;
+
+/// Report counts the moderation queue tells apart; content with more reports
+/// shares the top bucket
+const REPORT_BUCKETS: u32 = 10;
+
+/// Users a single post or comment may mention
+const MAX_MENTIONS: u32 = 10;
+
+/// Reports needed to auto-hide content unless the owner configures otherwise
+const DEFAULT_REPORT_THRESHOLD: u32 = 3;
+
+/// Reward history entries kept per user
+const REWARD_HISTORY_LEN: u32 = 50;
+
+/// Comments per user and day that earn reward points
+const MAX_REWARDED_COMMENTS_PER_DAY: u32 = 10;
+
+/// Previous revisions kept per post
+const POST_HISTORY_LEN: u32 = 20;
+
+/// Most recent approved posts considered when ranking a feed by score. Ranking
+/// reads every post in the window, so it stays well under the per-transaction
+/// limit on ledger entry reads.
+const RANKING_WINDOW: u32 = 25;
+
+/// Reputation earned for each approved post
+const APPROVED_POST_REPUTATION: i128 = 10;
+
+/// Registry functions the community contract relies on
+#[contractclient(name = "RegistryClient")]
+pub trait RegistryInterface {
+    fn is_registered(env: Env, user: Address) -> bool;
+    fn get_registered_at(env: Env, user: Address) -> u32;
 }
 
 /// Post status enum
-#[derive(Clone, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Copy)]
 #[contracttype]
 pub enum PostStatus {
     Pending,
@@ -40,8 +162,113 @@ pub enum PostStatus {
     Rejected,
 }
 
+/// Why content was reported
+#[derive(Clone, Debug, PartialEq, Copy)]
+#[contracttype]
+pub enum ReportReason {
+    Spam = 0,
+    Harassment = 1,
+    Misinformation = 2,
+    SelfHarm = 3,
+    Other = 4,
+}
+
+/// Kind of reported content
+#[derive(Clone, Debug, PartialEq, Copy)]
+#[contracttype]
+pub enum ReportTarget {
+    Post = 0,
+    Comment = 1,
+}
+
+/// Appeal status
+#[derive(Clone, Debug, PartialEq, Copy)]
+#[contracttype]
+pub enum AppealStatus {
+    Pending = 0,
+    Upheld = 1,   // Content restored
+    Rejected = 2,
+}
+
+/// Aggregated reports against a post or comment in the moderation queue
+#[derive(Clone)]
+#[contracttype]
+pub struct ReportSummary {
+    pub target_id: BytesN<32>,
+    pub target: ReportTarget,
+    pub report_count: u32,
+    pub last_reason: ReportReason,
+    pub first_reported: u64,
+}
+
+/// An author's appeal against hidden or removed content
+#[derive(Clone)]
+#[contracttype]
+pub struct Appeal {
+    pub target_id: BytesN<32>,
+    pub target: ReportTarget,
+    pub author: Address,
+    pub statement: String,
+    pub timestamp: u64,
+    pub status: AppealStatus,
+}
+
+/// Who may vote and how much each vote counts
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VotePolicy {
+    pub min_account_age_ledgers: u32,  // Ledgers since registry sign-up before a user may vote; 0 disables
+    pub reputation_per_weight: i128,   // Reputation needed for each vote weight above 1
+    pub max_vote_weight: i128,         // Upper bound on a single vote's weight
+    pub decay_period_ledgers: u32,     // Reputation halves after this many idle ledgers; 0 disables
+}
+
+impl VotePolicy {
+    fn default_policy() -> Self {
+        VotePolicy {
+            min_account_age_ledgers: 0,
+            reputation_per_weight: 50,
+            max_vote_weight: 5,
+            decay_period_ledgers: 120_960, // About a week at 5s ledgers
+        }
+    }
+}
+
+/// A user's reputation as of the last time it changed
+#[derive(Clone)]
+#[contracttype]
+pub struct Reputation {
+    pub score: i128,
+    pub updated_at: u32,    // Ledger sequence of the last update
+}
+
+/// A vote as cast, so it can be undone with the same weight
+#[derive(Clone)]
+#[contracttype]
+pub struct CastVote {
+    pub vote_type: VoteType,
+    pub weight: i128,
+}
+
+/// Feed ordering
+#[derive(Clone, Debug, PartialEq, Copy)]
+#[contracttype]
+pub enum FeedSort {
+    Newest = 0,
+    Top = 1,        // Net votes
+    Trending = 2,   // Net votes decayed by age
+}
+
+/// A page of posts and the cursor to pass for the next page (0 when exhausted)
+#[derive(Clone)]
+#[contracttype]
+pub struct PostPage {
+    pub posts: Vec<Post>,
+    pub next_cursor: u32,
+}
+
 /// Voting enum
-#[derive(Clone, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Copy)]
 #[contracttype]
 pub enum VoteType {
     Upvote,
@@ -59,24 +286,143 @@ pub struct Post {
     pub category: Symbol,        // Category (e.g., "education", "question")
     pub timestamp: u64,          // Timestamp
     pub status: PostStatus,      // Moderation status
-    pub upvotes: i128,           // Number of up votes
-    pub downvotes: i128,         // Number of down votes
-    pub approved: bool,          // Whether the post is approved
-    pub comments: Vec<BytesN<32>>, // IDs of comments on this post
+    pub upvotes: i128,           // Weighted up votes
+    pub downvotes: i128,         // Weighted down votes
+    pub approved: bool,          // Whether the post has ever been approved
+    pub comment_count: u32,        // Comments on this post, listed by `list_comments`
     pub is_removed: bool,          // For moderation
+    pub is_hidden: bool,           // Auto-hidden by reports pending review
+    pub anonymous: bool,           // Author is the contract itself; see `pseudonym`
+    pub pseudonym: BytesN<32>,     // Per-post pseudonym of an anonymous author, zero otherwise
+    pub edited_at: u64,            // 0 until the first edit
+    pub mentions: Vec<Address>,    // Users notified once the post is approved
+    pub verified_answers: u32,     // Comments marked as verified answers by experts
+}
+
+/// A managed category and its posting rules
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CategoryInfo {
+    pub symbol: Symbol,
+    pub display_name: String,
+    pub description: String,
+    pub requires_moderation: bool,  // New posts wait for approval; otherwise they go live at once
+    pub edit_requires_review: bool, // Editing an approved post sends it back to moderation
+    pub min_reputation: i128,       // Reputation needed to post; anonymous posts need 0
+    pub questions: bool,            // Posts are questions that experts can answer
+    pub archived: bool,             // Hidden from feeds and closed to new posts
+}
+
+/// Moderator tiers
+#[derive(Clone, Debug, PartialEq, Copy)]
+#[contracttype]
+pub enum ModeratorRole {
+    Category = 0,   // Moderates only the categories in its grant
+    Global = 1,     // Moderates everything and manages categories
+    Admin = 2,      // Global, and appoints category and global moderators
+}
+
+/// A moderator's role and, for category moderators, where it applies
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ModeratorGrant {
+    pub role: ModeratorRole,
+    pub categories: Vec<Symbol>,    // Empty unless the role is Category
+}
+
+/// A moderation decision
+#[derive(Clone, Debug, PartialEq, Copy)]
+#[contracttype]
+pub enum ModerationAction {
+    Approve = 0,
+    Reject = 1,
+    Remove = 2,
+    DismissReports = 3,
+    UpholdAppeal = 4,
+    RejectAppeal = 5,
+    DeleteComment = 6,
+}
+
+/// One entry in a post's or comment's public moderation log
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ModerationEntry {
+    pub moderator: Address,
+    pub action: ModerationAction,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+/// Something that earns or spends reward points
+#[derive(Clone, Debug, PartialEq, Copy)]
+#[contracttype]
+pub enum RewardAction {
+    PostApproved = 0,
+    CommentAdded = 1,
+    Redemption = 2,     // Points exchanged for tokens
+    CommentRemoved = 3, // Points for a removed comment taken back
+}
+
+impl RewardAction {
+    /// Points per action unless the owner sets a rate
+    fn default_rate(&self) -> i128 {
+        match self {
+            RewardAction::PostApproved => 10,
+            RewardAction::CommentAdded | RewardAction::Redemption | RewardAction::CommentRemoved => 0,
+        }
+    }
+}
+
+/// A change to a user's reward balance
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RewardEntry {
+    pub action: RewardAction,
+    pub amount: i128,       // Negative for redemptions and removed comments
+    pub balance: i128,      // Balance after the change
+    pub timestamp: u64,
+}
+
+/// A superseded version of a post
+#[derive(Clone)]
+#[contracttype]
+pub struct PostRevision {
+    pub title: String,
+    pub content: String,
+    pub timestamp: u64,     // When this version was written
 }
 
 /// A comment on a post
 #[derive(Clone)]
 #[contracttype]
 pub struct Comment {
+    pub id: BytesN<32>,
+    pub post_id: BytesN<32>,
+    pub parent_id: BytesN<32>, // Comment this replies to, or the post ID for top-level comments
     pub author: Address,
     pub content: String,
     pub timestamp: u64,
+    pub edited_at: u64,        // 0 until the first edit
     pub likes: i128,
+    pub replies: Vec<BytesN<32>>, // IDs of direct replies
     pub is_removed: bool,      // For moderation
+    pub is_hidden: bool,       // Auto-hidden by reports pending review
+    pub anonymous: bool,       // Author is the contract itself; see `pseudonym`
+    pub pseudonym: BytesN<32>, // Per-post pseudonym of an anonymous author, zero otherwise
+    pub verified_by: Vec<Address>, // Experts who marked this a verified answer
+}
+
+/// A verified healthcare professional badge
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ExpertBadge {
+    pub specialty: Symbol,
+    pub granted_at: u64,
 }
 
+/// Upper bound on the number of items returned by a paginated query
+const MAX_PAGE_SIZE: u32 = 50;
+
 #[contract]
 pub struct CommunityContract;
 
@@ -99,11 +445,9 @@ impl CommunityContract {
         env.storage().instance().set(&DataKey::Owner, &owner);
         env.storage().instance().set(&DataKey::RegistryContract, &registry_contract);
         
-        // Initialize post index
-        env.storage().instance().set(&DataKey::PostIndex, &Vec::<BytesN<32>>::new(&env));
-        
-        // Add owner as a moderator
-        env.storage().instance().set(&DataKey::Moderator(owner.clone()), &true);
+        // Add owner as an admin moderator
+        let grant = ModeratorGrant { role: ModeratorRole::Admin, categories: Vec::new(&env) };
+        env.storage().instance().set(&DataKey::Moderator(owner.clone()), &grant);
         
         // Log initialization
         env.events().publish(
@@ -114,14 +458,14 @@ impl CommunityContract {
         Ok(())
     }
     
-    /// Generate a random post ID
-    fn generate_post_id(env: &Env) -> BytesN<32> {
-        // Create a random seed based on the timestamp
-        let timestamp = env.ledger().timestamp();
-        let random_bytes = timestamp.to_be_bytes();
+    /// Generate a unique post or comment ID
+    fn generate_id(env: &Env) -> BytesN<32> {
+        // Mix a running counter into the timestamp so IDs created in the same ledger differ
+        let nonce = env.storage().instance().get::<DataKey, u64>(&DataKey::IdNonce).unwrap_or_default();
+        env.storage().instance().set(&DataKey::IdNonce, &(nonce + 1));
         
-        // Create a BytesN object from the random bytes for hashing
-        let bytes_to_hash = Bytes::from_array(env, &random_bytes);
+        let mut bytes_to_hash = Bytes::from_array(env, &env.ledger().timestamp().to_be_bytes());
+        bytes_to_hash.extend_from_array(&nonce.to_be_bytes());
         
         // Hash the bytes to get a deterministic but random-like BytesN<32>
         env.crypto().sha256(&bytes_to_hash)
@@ -134,62 +478,111 @@ impl CommunityContract {
         title: String,
         content: String,
         category: Symbol,
+        mentions: Vec<Address>,
     ) -> Result<BytesN<32>, CommunityError> {
         author.require_auth();
         
+        if mentions.len() > MAX_MENTIONS {
+            return Err(CommunityError::TooManyMentions);
+        }
+        let rules = Self::check_category(&env, &category, Some(&author))?;
+        let post_id = Self::insert_post(&env, author.clone(), title, content, &rules, mentions, None);
+        
+        // Update user posts
+        Self::index_push(&env, IndexKey::UserPosts(author.clone()), post_id.clone());
+        
+        env.events().publish(
+            (symbol_short!("create"), symbol_short!("post")),
+            (author, post_id.clone(), category)
+        );
+        
+        Ok(post_id)
+    }
+    
+    /// Create a post without linking it to an account. No account authorizes the
+    /// call, so it can be relayed: `author_key` is an ed25519 key the author
+    /// derives from a secret for this post only, and `signature` over
+    /// `anonymous_post_message` proves they hold it. Later signatures from the same
+    /// key prove authorship. `identity_commitment` is sha256(author XDR || salt) and
+    /// `identity_escrow` holds the author and salt encrypted to the owner; the
+    /// commitment lets `reveal_anonymous_author` check what the escrow decrypts to.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_anonymous_post(
+        env: Env,
+        title: String,
+        content: String,
+        category: Symbol,
+        author_key: BytesN<32>,
+        identity_commitment: BytesN<32>,
+        identity_escrow: Bytes,
+        signature: BytesN<64>,
+    ) -> Result<BytesN<32>, CommunityError> {
+        let rules = Self::check_category(&env, &category, None)?;
+        
+        // Traps on an invalid signature
+        let message = Self::anonymous_post_message(env.clone(), title.clone(), content.clone(), category.clone(), author_key.clone(), identity_commitment.clone(), identity_escrow.clone());
+        Self::consume_author_key(&env, &author_key, &message, &signature);
+        
+        let post_id = Self::generate_id(&env);
+        let pseudonym = Self::derive_pseudonym(&env, &author_key, &post_id);
+        Self::store_author_key(&env, &post_id, author_key, identity_commitment, identity_escrow);
+        
+        let post_id = Self::insert_post(&env, env.current_contract_address(), title, content, &rules, Vec::new(&env), Some((post_id, pseudonym.clone())));
+        
+        env.events().publish(
+            (symbol_short!("create"), symbol_short!("anonpost")),
+            (pseudonym, post_id.clone(), category)
+        );
+        
+        Ok(post_id)
+    }
+    
+    /// Store a new pending post and index it; `anonymous` carries a pre-generated
+    /// ID and the author's pseudonym
+    fn insert_post(
+        env: &Env,
+        author: Address,
+        title: String,
+        content: String,
+        rules: &CategoryInfo,
+        mentions: Vec<Address>,
+        anonymous: Option<(BytesN<32>, BytesN<32>)>,
+    ) -> BytesN<32> {
+        let category = rules.symbol.clone();
         let timestamp = env.ledger().timestamp();
-        let post_id = Self::generate_post_id(&env);
+        let (post_id, pseudonym) = match anonymous.clone() {
+            Some((post_id, pseudonym)) => (post_id, pseudonym),
+            None => (Self::generate_id(env), BytesN::from_array(env, &[0; 32])),
+        };
         
         let post = Post {
             id: post_id.clone(),
             title,
             content,
-            author: author.clone(),
+            author,
             category: category.clone(),
             timestamp,
-            status: PostStatus::Pending,
+            status: if rules.requires_moderation { PostStatus::Pending } else { PostStatus::Approved },
             upvotes: 0,
             downvotes: 0,
-            approved: false,
-            comments: Vec::new(&env),
+            approved: !rules.requires_moderation,
+            comment_count: 0,
             is_removed: false,
+            is_hidden: false,
+            anonymous: anonymous.is_some(),
+            pseudonym,
+            edited_at: 0,
+            mentions,
+            verified_answers: 0,
         };
         
-        env.storage().instance().set(&DataKey::Post(post_id.clone()), &post);
-        
-        // Update post index
-        let post_index = env.storage().instance().get::<DataKey, Vec<BytesN<32>>>(&DataKey::PostIndex);
-        let mut post_index = match post_index {
-            Some(index) => index,
-            None => Vec::new(&env),
-        };
-        post_index.push_back(post_id.clone());
-        env.storage().instance().set(&DataKey::PostIndex, &post_index);
-        
-        // Update user posts
-        let user_posts = env.storage().instance().get::<DataKey, Vec<BytesN<32>>>(&DataKey::UserPosts(author.clone()));
-        let mut user_posts = match user_posts {
-            Some(posts) => posts,
-            None => Vec::new(&env),
-        };
-        user_posts.push_back(post_id.clone());
-        env.storage().instance().set(&DataKey::UserPosts(author.clone()), &user_posts);
-        
-        // Update category posts
-        let category_posts = env.storage().instance().get::<DataKey, Vec<BytesN<32>>>(&DataKey::Category(category.clone()));
-        let mut category_posts = match category_posts {
-            Some(posts) => posts,
-            None => Vec::new(&env),
-        };
-        category_posts.push_back(post_id.clone());
-        env.storage().instance().set(&DataKey::Category(category.clone()), &category_posts);
-        
-        env.events().publish(
-            (symbol_short!("create"), symbol_short!("post")),
-            (author, post_id.clone(), category)
-        );
+        env.storage().persistent().set(&DataKey::Post(post_id.clone()), &post);
+        if post.approved {
+            Self::index_approved(env, &post);
+            Self::announce_post(env, &post);
+        }
         
-        Ok(post_id)
+        post_id
     }
     
     /// Moderate a post (approve or reject)
@@ -198,26 +591,38 @@ impl CommunityContract {
         moderator: Address,
         post_id: BytesN<32>,
         approve: bool,
+        reason: String,
     ) -> Result<(), CommunityError> {
-        if !Self::is_moderator(&env, moderator.clone()) {
-            return Err(CommunityError::NotModerator);
-        }
+        let grant = Self::get_moderator(env.clone(), moderator.clone())?;
         
         moderator.require_auth();
         
-        let post = env.storage().instance().get::<DataKey, Post>(&DataKey::Post(post_id.clone()));
+        let post = env.storage().persistent().get::<DataKey, Post>(&DataKey::Post(post_id.clone()));
         let mut post = match post {
             Some(post) => post,
             None => return Err(CommunityError::PostNotFound),
         };
+        Self::check_scope(&grant, Some(&post.category))?;
         
         post.status = if approve { PostStatus::Approved } else { PostStatus::Rejected };
         
-        env.storage().instance().set(&DataKey::Post(post_id.clone()), &post);
+        env.storage().persistent().set(&DataKey::Post(post_id.clone()), &post);
         
-        if approve {
-            Self::award_author(env.clone(), post.author.clone())?;
+        // Rewarding an anonymous post would tie it to an account. Re-approval
+        // after an edit earns nothing further.
+        if approve && !post.approved && !post.anonymous {
+            Self::award(&env, &post.author, RewardAction::PostApproved);
+            Self::adjust_reputation(&env, &post.author, APPROVED_POST_REPUTATION);
         }
+        if approve && !post.approved {
+            Self::index_approved(&env, &post);
+            Self::announce_post(&env, &post);
+            post.approved = true;
+            env.storage().persistent().set(&DataKey::Post(post_id.clone()), &post);
+        }
+        
+        let action = if approve { ModerationAction::Approve } else { ModerationAction::Reject };
+        Self::log_moderation(&env, &post_id, &moderator, action, reason);
         
         env.events().publish(
             (if approve { symbol_short!("approve") } else { symbol_short!("reject") }, symbol_short!("post")), 
@@ -227,283 +632,3071 @@ impl CommunityContract {
         Ok(())
     }
     
-    /// Add a moderator
-    pub fn add_moderator(
+    /// Edit a post (author only). Votes are kept; the previous version goes to the
+    /// edit history, and an approved post returns to moderation if its category says so.
+    pub fn edit_post(
         env: Env,
-        owner: Address,
-        new_moderator: Address,
+        author: Address,
+        post_id: BytesN<32>,
+        title: String,
+        content: String,
     ) -> Result<(), CommunityError> {
-        let contract_owner = Self::get_owner_internal(&env)?;
-        if owner != contract_owner {
+        author.require_auth();
+        
+        let mut post = Self::get_post(env.clone(), post_id.clone())?;
+        if post.author != author || post.anonymous {
             return Err(CommunityError::Unauthorized);
         }
+        if post.is_removed {
+            return Err(CommunityError::PostNotFound);
+        }
         
-        owner.require_auth();
-        
-        env.storage().instance().set(&DataKey::Moderator(new_moderator.clone()), &true);
+        Self::apply_post_edit(&env, &mut post, title, content);
         
         env.events().publish(
-            (symbol_short!("add"), symbol_short!("mod")),
-            (owner, new_moderator)
+            (symbol_short!("edit"), symbol_short!("post")),
+            (author, post_id)
         );
         
         Ok(())
     }
     
-    /// Remove a moderator
-    pub fn remove_moderator(
+    /// Edit the body of an anonymous post with a proof of authorship
+    pub fn edit_anonymous_post(
         env: Env,
-        owner: Address,
-        moderator_to_remove: Address,
+        post_id: BytesN<32>,
+        content: String,
+        signature: BytesN<64>,
     ) -> Result<(), CommunityError> {
-        let contract_owner = Self::get_owner_internal(&env)?;
-        if owner != contract_owner {
-            return Err(CommunityError::Unauthorized);
+        let mut post = Self::get_post(env.clone(), post_id.clone())?;
+        if post.is_removed {
+            return Err(CommunityError::PostNotFound);
         }
+        Self::verify_authorship(&env, &post_id, symbol_short!("edit"), content.clone(), &signature)?;
         
-        owner.require_auth();
-        
-        env.storage().instance().remove(&DataKey::Moderator(moderator_to_remove.clone()));
+        let title = post.title.clone();
+        Self::apply_post_edit(&env, &mut post, title, content);
         
         env.events().publish(
-            (symbol_short!("remove"), symbol_short!("mod")),
-            (owner, moderator_to_remove)
+            (symbol_short!("edit"), symbol_short!("post")),
+            (post.pseudonym, post_id)
         );
         
         Ok(())
     }
     
-    /// Vote on a post
-    pub fn vote(
+    fn apply_post_edit(env: &Env, post: &mut Post, title: String, content: String) {
+        let mut history = env.storage().persistent().get::<DataKey, Vec<PostRevision>>(&DataKey::PostHistory(post.id.clone())).unwrap_or(Vec::new(env));
+        if history.len() >= POST_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(PostRevision {
+            title: post.title.clone(),
+            content: post.content.clone(),
+            timestamp: if post.edited_at == 0 { post.timestamp } else { post.edited_at },
+        });
+        env.storage().persistent().set(&DataKey::PostHistory(post.id.clone()), &history);
+        
+        post.title = title;
+        post.content = content;
+        post.edited_at = env.ledger().timestamp();
+        let edit_requires_review = Self::get_category(env.clone(), post.category.clone())
+            .map(|rules| rules.edit_requires_review)
+            .unwrap_or(true);
+        if post.status == PostStatus::Approved && edit_requires_review {
+            post.status = PostStatus::Pending;
+        }
+        
+        env.storage().persistent().set(&DataKey::Post(post.id.clone()), &*post);
+    }
+    
+    /// Previous versions of a post, oldest first. Only the last POST_HISTORY_LEN are kept.
+    pub fn get_post_history(env: Env, post_id: BytesN<32>) -> Vec<PostRevision> {
+        env.storage().persistent().get::<DataKey, Vec<PostRevision>>(&DataKey::PostHistory(post_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    /// Retract a post (author only). The post is soft-deleted like a moderator removal.
+    pub fn delete_post(
         env: Env,
-        user: Address,
+        author: Address,
         post_id: BytesN<32>,
-        vote_type: VoteType,
     ) -> Result<(), CommunityError> {
-        user.require_auth();
-        
-        let post = env.storage().instance().get::<DataKey, Post>(&DataKey::Post(post_id.clone()));
-        let mut post = match post {
-            Some(post) => post,
-            None => return Err(CommunityError::PostNotFound),
-        };
+        author.require_auth();
         
-        if post.status != PostStatus::Approved {
-            return Err(CommunityError::PostNotApproved);
+        let mut post = Self::get_post(env.clone(), post_id.clone())?;
+        if post.author != author || post.anonymous {
+            return Err(CommunityError::Unauthorized);
         }
-        
-        let previous_vote = env.storage().instance().get::<DataKey, VoteType>(&DataKey::Vote(post_id.clone(), user.clone()));
-
-        if let Some(prev_vote) = previous_vote {
-            if prev_vote == vote_type {
-                // Cancel the vote
-                if prev_vote == VoteType::Upvote { post.upvotes -= 1; } 
-                else { post.downvotes -= 1; }
-                env.storage().instance().remove(&DataKey::Vote(post_id.clone(), user.clone()));
-            } else {
-                // Change vote type
-                if vote_type == VoteType::Upvote { post.downvotes -= 1; post.upvotes += 1; }
-                else { post.upvotes -= 1; post.downvotes += 1; }
-                env.storage().instance().set(&DataKey::Vote(post_id.clone(), user.clone()), &vote_type);
-            }
-        } else {
-            // New vote
-            if vote_type == VoteType::Upvote { post.upvotes += 1; }
-            else { post.downvotes += 1; }
-            env.storage().instance().set(&DataKey::Vote(post_id.clone(), user.clone()), &vote_type);
+        if post.is_removed {
+            return Err(CommunityError::PostNotFound);
         }
         
-        env.storage().instance().set(&DataKey::Post(post_id.clone()), &post);
+        post.is_removed = true;
+        env.storage().persistent().set(&DataKey::Post(post_id.clone()), &post);
+        Self::dequeue_reports(&env, &post_id);
+        
         env.events().publish(
-            (symbol_short!("vote"), symbol_short!("cast")),
-            (user, post_id, vote_type)
+            (symbol_short!("delete"), symbol_short!("post")),
+            (author, post_id)
         );
+        
         Ok(())
     }
     
-    /// Get a post by ID
-    pub fn get_post(
+    /// Add a category (global moderators and admins)
+    pub fn create_category(
         env: Env,
-        post_id: BytesN<32>,
-    ) -> Result<Post, CommunityError> {
-        let post = env.storage().instance().get::<DataKey, Post>(&DataKey::Post(post_id));
-        match post {
-            Some(post) => Ok(post),
-            None => Err(CommunityError::PostNotFound),
+        moderator: Address,
+        category: CategoryInfo,
+    ) -> Result<(), CommunityError> {
+        let grant = Self::get_moderator(env.clone(), moderator.clone())?;
+        Self::check_scope(&grant, None)?;
+        
+        moderator.require_auth();
+        
+        let key = DataKey::CategoryInfo(category.symbol.clone());
+        if env.storage().instance().has(&key) {
+            return Err(CommunityError::CategoryExists);
         }
-    }
-    
-    /// Get a comment by ID
-    pub fn get_comment(
-        env: Env,
-        comment_id: BytesN<32>,
-    ) -> Result<Comment, CommunityError> {
-        let comment = env.storage().instance().get::<DataKey, Comment>(&DataKey::Comment(comment_id));
-        match comment {
-            Some(comment) => Ok(comment),
-            None => Err(CommunityError::CommentNotFound),
+        if category.min_reputation < 0 {
+            return Err(CommunityError::InvalidPolicy);
         }
-    }
-    
-    /// List all approved posts
-    pub fn list_approved_posts(env: Env) -> Result<Vec<BytesN<32>>, CommunityError> {
-        let post_index = env.storage().instance().get::<DataKey, Vec<BytesN<32>>>(&DataKey::PostIndex);
-        let post_index = match post_index {
-            Some(index) => index,
-            None => return Ok(Vec::new(&env)),
-        };
         
-        let mut approved_posts = Vec::new(&env);
+        let category = CategoryInfo { archived: false, ..category };
+        env.storage().instance().set(&key, &category);
         
-        for i in 0..post_index.len() {
-            let post_id = post_index.get_unchecked(i);
-            if let Some(post) = env.storage().instance().get::<DataKey, Post>(&DataKey::Post(post_id.clone())) {
-                if post.status == PostStatus::Approved {
-                    approved_posts.push_back(post_id.clone());
-                }
-            }
-        }
+        let mut categories = env.storage().instance().get::<DataKey, Vec<Symbol>>(&DataKey::CategoryList).unwrap_or(Vec::new(&env));
+        categories.push_back(category.symbol.clone());
+        env.storage().instance().set(&DataKey::CategoryList, &categories);
         
-        Ok(approved_posts)
-    }
-    
-    /// List posts by category
-    pub fn list_posts_by_category(env: Env, category: Symbol) -> Result<Vec<BytesN<32>>, CommunityError> {
-        let category_posts = env.storage().instance().get::<DataKey, Vec<BytesN<32>>>(&DataKey::Category(category));
-        match category_posts {
-            Some(posts) => Ok(posts),
-            None => Ok(Vec::new(&env)),
-        }
-    }
-    
-    /// List posts by user
-    pub fn list_posts_by_user(env: Env, user: Address) -> Result<Vec<BytesN<32>>, CommunityError> {
-        let user_posts = env.storage().instance().get::<DataKey, Vec<BytesN<32>>>(&DataKey::UserPosts(user));
-        match user_posts {
-            Some(posts) => Ok(posts),
-            None => Ok(Vec::new(&env)),
-        }
+        env.events().publish(
+            (symbol_short!("create"), symbol_short!("category")),
+            (moderator, category.symbol)
+        );
+        
+        Ok(())
     }
     
-    /// Award tokens to an author (internal function)
-    fn award_author(
+    /// Change a category's name, description or posting rules (global moderators and admins).
+    /// Archiving goes through `archive_category`.
+    pub fn update_category(
         env: Env,
-        author: Address,
+        moderator: Address,
+        category: CategoryInfo,
     ) -> Result<(), CommunityError> {
-        let current_reward = env.storage().instance().get::<DataKey, i128>(&DataKey::Reward(author.clone()));
-        let current_reward = match current_reward {
-            Some(reward) => reward,
-            None => 0,
-        };
+        let grant = Self::get_moderator(env.clone(), moderator.clone())?;
+        Self::check_scope(&grant, None)?;
+        
+        moderator.require_auth();
         
-        let new_reward = current_reward + 10;
-        env.storage().instance().set(&DataKey::Reward(author.clone()), &new_reward);
+        let existing = Self::get_category(env.clone(), category.symbol.clone())?;
+        if category.min_reputation < 0 {
+            return Err(CommunityError::InvalidPolicy);
+        }
+        
+        let category = CategoryInfo { archived: existing.archived, ..category };
+        env.storage().instance().set(&DataKey::CategoryInfo(category.symbol.clone()), &category);
         
         env.events().publish(
-            (symbol_short!("reward"), symbol_short!("author")),
-            (author, new_reward)
+            (symbol_short!("update"), symbol_short!("category")),
+            (moderator, category.symbol)
         );
         
         Ok(())
     }
     
-    /// Get user's reward balance
+    /// Archive or restore a category (global moderators and admins). Archived categories drop
+    /// out of feeds and take no new posts; their posts are kept.
+    pub fn archive_category(
+        env: Env,
+        moderator: Address,
+        category: Symbol,
+        archived: bool,
+    ) -> Result<(), CommunityError> {
+        let grant = Self::get_moderator(env.clone(), moderator.clone())?;
+        Self::check_scope(&grant, None)?;
+        
+        moderator.require_auth();
+        
+        let mut info = Self::get_category(env.clone(), category.clone())?;
+        info.archived = archived;
+        env.storage().instance().set(&DataKey::CategoryInfo(category.clone()), &info);
+        
+        env.events().publish(
+            (if archived { symbol_short!("archive") } else { symbol_short!("restore") }, symbol_short!("category")),
+            (moderator, category)
+        );
+        
+        Ok(())
+    }
+    
+    /// Get a category's details
+    pub fn get_category(env: Env, category: Symbol) -> Result<CategoryInfo, CommunityError> {
+        match env.storage().instance().get::<DataKey, CategoryInfo>(&DataKey::CategoryInfo(category)) {
+            Some(info) => Ok(info),
+            None => Err(CommunityError::CategoryNotFound),
+        }
+    }
+    
+    /// List all categories in creation order, archived ones included
+    pub fn list_categories(env: Env) -> Vec<CategoryInfo> {
+        let categories = env.storage().instance().get::<DataKey, Vec<Symbol>>(&DataKey::CategoryList).unwrap_or(Vec::new(&env));
+        
+        let mut result = Vec::new(&env);
+        for category in categories.iter() {
+            if let Some(info) = env.storage().instance().get::<DataKey, CategoryInfo>(&DataKey::CategoryInfo(category)) {
+                result.push_back(info);
+            }
+        }
+        
+        result
+    }
+    
+    /// Check that a category accepts a new post from `author` (None for anonymous posts)
+    fn check_category(env: &Env, category: &Symbol, author: Option<&Address>) -> Result<CategoryInfo, CommunityError> {
+        let rules = Self::get_category(env.clone(), category.clone())?;
+        if rules.archived {
+            return Err(CommunityError::CategoryArchived);
+        }
+        
+        let reputation = match author {
+            Some(author) => Self::get_reputation(env.clone(), author.clone()),
+            None => 0,
+        };
+        if reputation < rules.min_reputation {
+            return Err(CommunityError::InsufficientReputation);
+        }
+        
+        Ok(rules)
+    }
+    
+    /// Report a post. Enough reports hide it until a moderator reviews it.
+    pub fn report_post(
+        env: Env,
+        reporter: Address,
+        post_id: BytesN<32>,
+        reason: ReportReason,
+    ) -> Result<u32, CommunityError> {
+        reporter.require_auth();
+        
+        let mut post = Self::get_post(env.clone(), post_id.clone())?;
+        if post.is_removed {
+            return Err(CommunityError::PostNotFound);
+        }
+        
+        let (count, hide) = Self::file_report(&env, &reporter, &post_id, ReportTarget::Post, reason)?;
+        if hide && !post.is_hidden {
+            post.is_hidden = true;
+            env.storage().persistent().set(&DataKey::Post(post_id.clone()), &post);
+        }
+        
+        Ok(count)
+    }
+    
+    /// Report a comment. Enough reports hide it until a moderator reviews it.
+    pub fn report_comment(
+        env: Env,
+        reporter: Address,
+        comment_id: BytesN<32>,
+        reason: ReportReason,
+    ) -> Result<u32, CommunityError> {
+        reporter.require_auth();
+        
+        let mut comment = Self::get_comment(env.clone(), comment_id.clone())?;
+        if comment.is_removed {
+            return Err(CommunityError::CommentRemoved);
+        }
+        
+        let (count, hide) = Self::file_report(&env, &reporter, &comment_id, ReportTarget::Comment, reason)?;
+        if hide && !comment.is_hidden {
+            comment.is_hidden = true;
+            env.storage().persistent().set(&DataKey::Comment(comment_id.clone()), &comment);
+        }
+        
+        Ok(count)
+    }
+    
+    /// Record one report per reporter and move the target into its report-count bucket.
+    /// Reporters must pass the same registry and account-age checks as voters.
+    /// Returns the new count and whether it reached the auto-hide threshold.
+    fn file_report(
+        env: &Env,
+        reporter: &Address,
+        target_id: &BytesN<32>,
+        target: ReportTarget,
+        reason: ReportReason,
+    ) -> Result<(u32, bool), CommunityError> {
+        let policy = Self::get_vote_policy(env.clone());
+        Self::check_voter(env, reporter, &policy)?;
+        
+        let report_key = DataKey::Report(target_id.clone(), reporter.clone());
+        if env.storage().persistent().has(&report_key) {
+            return Err(CommunityError::AlreadyReported);
+        }
+        env.storage().persistent().set(&report_key, &reason);
+        
+        let summary = env.storage().persistent().get::<DataKey, ReportSummary>(&DataKey::ReportSummary(target_id.clone()));
+        let mut summary = match summary {
+            Some(summary) => summary,
+            None => {
+                ReportSummary {
+                    target_id: target_id.clone(),
+                    target,
+                    report_count: 0,
+                    last_reason: reason,
+                    first_reported: env.ledger().timestamp(),
+                }
+            }
+        };
+        let old_bucket = summary.report_count.min(REPORT_BUCKETS);
+        summary.report_count += 1;
+        summary.last_reason = reason;
+        env.storage().persistent().set(&DataKey::ReportSummary(target_id.clone()), &summary);
+        
+        let new_bucket = summary.report_count.min(REPORT_BUCKETS);
+        if new_bucket != old_bucket {
+            if old_bucket > 0 {
+                Self::queue_remove(env, IndexKey::ReportQueue(old_bucket), target_id);
+            }
+            Self::queue_push(env, IndexKey::ReportQueue(new_bucket), target_id.clone());
+        }
+        
+        env.events().publish(
+            (symbol_short!("report"), target),
+            (reporter.clone(), target_id.clone(), reason)
+        );
+        
+        let threshold = Self::get_report_threshold(env.clone());
+        Ok((summary.report_count, summary.report_count >= threshold))
+    }
+    
+    /// Drop an entry from the moderation queue once it has been dealt with
+    fn dequeue_reports(env: &Env, target_id: &BytesN<32>) {
+        let summary = env.storage().persistent().get::<DataKey, ReportSummary>(&DataKey::ReportSummary(target_id.clone()));
+        let summary = match summary {
+            Some(summary) => summary,
+            None => return,
+        };
+        env.storage().persistent().remove(&DataKey::ReportSummary(target_id.clone()));
+        Self::queue_remove(env, IndexKey::ReportQueue(summary.report_count.min(REPORT_BUCKETS)), target_id);
+    }
+    
+    /// List reported content, most reported first, starting at index `cursor`.
+    /// Content with `REPORT_BUCKETS` or more reports is listed first in no particular order.
+    pub fn list_moderation_queue(env: Env, cursor: u32, limit: u32) -> Vec<ReportSummary> {
+        let mut remaining = limit.min(MAX_PAGE_SIZE);
+        let mut skip = cursor;
+        let mut result = Vec::new(&env);
+        
+        // Walk the buckets from the most reported down, treating them as one list
+        for bucket in (1..=REPORT_BUCKETS).rev() {
+            if remaining == 0 {
+                break;
+            }
+            let index = IndexKey::ReportQueue(bucket);
+            let len = Self::index_len(&env, &index);
+            if skip >= len {
+                skip -= len;
+                continue;
+            }
+            let end = skip.saturating_add(remaining).min(len);
+            for target_id in Self::index_range(&env, &index, skip, end).iter() {
+                if let Some(summary) = env.storage().persistent().get::<DataKey, ReportSummary>(&DataKey::ReportSummary(target_id)) {
+                    result.push_back(summary);
+                }
+            }
+            remaining -= end - skip;
+            skip = 0;
+        }
+        
+        result
+    }
+    
+    /// Remove a post (moderator only), including one that was already approved
+    pub fn remove_post(
+        env: Env,
+        moderator: Address,
+        post_id: BytesN<32>,
+        reason: String,
+    ) -> Result<(), CommunityError> {
+        let grant = Self::get_moderator(env.clone(), moderator.clone())?;
+        
+        moderator.require_auth();
+        
+        let mut post = Self::get_post(env.clone(), post_id.clone())?;
+        Self::check_scope(&grant, Some(&post.category))?;
+        post.is_removed = true;
+        env.storage().persistent().set(&DataKey::Post(post_id.clone()), &post);
+        Self::dequeue_reports(&env, &post_id);
+        Self::log_moderation(&env, &post_id, &moderator, ModerationAction::Remove, reason);
+        
+        env.events().publish(
+            (symbol_short!("remove"), symbol_short!("post")),
+            (moderator, post_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Dismiss the reports against a post or comment and unhide it (moderator only)
+    pub fn dismiss_reports(
+        env: Env,
+        moderator: Address,
+        target_id: BytesN<32>,
+        reason: String,
+    ) -> Result<(), CommunityError> {
+        let grant = Self::get_moderator(env.clone(), moderator.clone())?;
+        
+        moderator.require_auth();
+        
+        let summary = env.storage().persistent().get::<DataKey, ReportSummary>(&DataKey::ReportSummary(target_id.clone()));
+        let summary = match summary {
+            Some(summary) => summary,
+            None => return Err(CommunityError::ReportNotFound),
+        };
+        Self::check_scope(&grant, Some(&Self::target_category(&env, &target_id, summary.target)?))?;
+        
+        Self::set_visibility(&env, &target_id, summary.target, false)?;
+        Self::dequeue_reports(&env, &target_id);
+        Self::log_moderation(&env, &target_id, &moderator, ModerationAction::DismissReports, reason);
+        
+        env.events().publish(
+            (symbol_short!("dismiss"), summary.target),
+            (moderator, target_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Appeal against a hidden or removed post or comment (author only)
+    pub fn appeal(
+        env: Env,
+        author: Address,
+        target_id: BytesN<32>,
+        target: ReportTarget,
+        statement: String,
+    ) -> Result<(), CommunityError> {
+        author.require_auth();
+        
+        let (content_author, is_removed, is_hidden) = match target {
+            ReportTarget::Post => {
+                let post = Self::get_post(env.clone(), target_id.clone())?;
+                (post.author, post.is_removed, post.is_hidden)
+            }
+            ReportTarget::Comment => {
+                let comment = Self::get_comment(env.clone(), target_id.clone())?;
+                (comment.author, comment.is_removed, comment.is_hidden)
+            }
+        };
+        if content_author != author {
+            return Err(CommunityError::Unauthorized);
+        }
+        if !is_removed && !is_hidden {
+            return Err(CommunityError::NotRemoved);
+        }
+        
+        if let Some(existing) = env.storage().persistent().get::<DataKey, Appeal>(&DataKey::Appeal(target_id.clone())) {
+            if existing.status == AppealStatus::Pending || existing.status == AppealStatus::Rejected {
+                return Err(CommunityError::AlreadyAppealed);
+            }
+        }
+        
+        let appeal = Appeal {
+            target_id: target_id.clone(),
+            target,
+            author: author.clone(),
+            statement,
+            timestamp: env.ledger().timestamp(),
+            status: AppealStatus::Pending,
+        };
+        env.storage().persistent().set(&DataKey::Appeal(target_id.clone()), &appeal);
+        
+        Self::queue_push(&env, IndexKey::AppealQueue, target_id.clone());
+        
+        env.events().publish(
+            (symbol_short!("appeal"), target),
+            (author, target_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Decide a pending appeal, restoring the content when upheld (moderator only)
+    pub fn resolve_appeal(
+        env: Env,
+        moderator: Address,
+        target_id: BytesN<32>,
+        restore: bool,
+        reason: String,
+    ) -> Result<(), CommunityError> {
+        let grant = Self::get_moderator(env.clone(), moderator.clone())?;
+        
+        moderator.require_auth();
+        
+        let appeal = env.storage().persistent().get::<DataKey, Appeal>(&DataKey::Appeal(target_id.clone()));
+        let mut appeal = match appeal {
+            Some(appeal) if appeal.status == AppealStatus::Pending => appeal,
+            _ => return Err(CommunityError::AppealNotFound),
+        };
+        Self::check_scope(&grant, Some(&Self::target_category(&env, &target_id, appeal.target)?))?;
+        
+        if restore {
+            Self::set_visibility(&env, &target_id, appeal.target, false)?;
+            Self::dequeue_reports(&env, &target_id);
+        }
+        appeal.status = if restore { AppealStatus::Upheld } else { AppealStatus::Rejected };
+        env.storage().persistent().set(&DataKey::Appeal(target_id.clone()), &appeal);
+        
+        Self::queue_remove(&env, IndexKey::AppealQueue, &target_id);
+        
+        let action = if restore { ModerationAction::UpholdAppeal } else { ModerationAction::RejectAppeal };
+        Self::log_moderation(&env, &target_id, &moderator, action, reason);
+        
+        env.events().publish(
+            (symbol_short!("appeal"), symbol_short!("resolved")),
+            (moderator, target_id, restore)
+        );
+        
+        Ok(())
+    }
+    
+    /// Get the appeal filed for a post or comment
+    pub fn get_appeal(env: Env, target_id: BytesN<32>) -> Result<Appeal, CommunityError> {
+        match env.storage().persistent().get::<DataKey, Appeal>(&DataKey::Appeal(target_id)) {
+            Some(appeal) => Ok(appeal),
+            None => Err(CommunityError::AppealNotFound),
+        }
+    }
+    
+    /// List pending appeals, starting at index `cursor`. Appeals are listed in
+    /// filing order until one is resolved, which moves the newest into its place.
+    pub fn list_appeals(env: Env, cursor: u32, limit: u32) -> Vec<Appeal> {
+        let index = IndexKey::AppealQueue;
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(Self::index_len(&env, &index));
+        
+        let mut result = Vec::new(&env);
+        for target_id in Self::index_range(&env, &index, cursor, end).iter() {
+            if let Some(appeal) = env.storage().persistent().get::<DataKey, Appeal>(&DataKey::Appeal(target_id)) {
+                result.push_back(appeal);
+            }
+        }
+        
+        result
+    }
+    
+    /// Hide or restore a post or comment; restoring also clears removal
+    fn set_visibility(env: &Env, target_id: &BytesN<32>, target: ReportTarget, hidden: bool) -> Result<(), CommunityError> {
+        match target {
+            ReportTarget::Post => {
+                let mut post = Self::get_post(env.clone(), target_id.clone())?;
+                post.is_hidden = hidden;
+                if !hidden {
+                    post.is_removed = false;
+                }
+                env.storage().persistent().set(&DataKey::Post(target_id.clone()), &post);
+            }
+            ReportTarget::Comment => {
+                let mut comment = Self::get_comment(env.clone(), target_id.clone())?;
+                let restored = !hidden && comment.is_removed;
+                comment.is_hidden = hidden;
+                if !hidden {
+                    comment.is_removed = false;
+                }
+                env.storage().persistent().set(&DataKey::Comment(target_id.clone()), &comment);
+                if restored {
+                    Self::settle_comment_reward(env, &comment, true);
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// Set how many reports auto-hide content (owner only)
+    pub fn set_report_threshold(
+        env: Env,
+        owner: Address,
+        threshold: u32,
+    ) -> Result<(), CommunityError> {
+        let contract_owner = Self::get_owner_internal(&env)?;
+        if owner != contract_owner {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        owner.require_auth();
+        
+        if threshold == 0 {
+            return Err(CommunityError::InvalidThreshold);
+        }
+        env.storage().instance().set(&DataKey::ReportThreshold, &threshold);
+        
+        Ok(())
+    }
+    
+    /// Get how many reports auto-hide content
+    pub fn get_report_threshold(env: Env) -> u32 {
+        env.storage().instance().get::<DataKey, u32>(&DataKey::ReportThreshold).unwrap_or(DEFAULT_REPORT_THRESHOLD)
+    }
+    
+    /// Appoint a moderator or change its role (admins only; only the owner appoints admins).
+    /// Category moderators act only within `categories`.
+    pub fn add_moderator(
+        env: Env,
+        admin: Address,
+        new_moderator: Address,
+        role: ModeratorRole,
+        categories: Vec<Symbol>,
+    ) -> Result<(), CommunityError> {
+        // Changing an existing admin's grant is as sensitive as creating one
+        let current_is_admin = env.storage().instance().get::<DataKey, ModeratorGrant>(&DataKey::Moderator(new_moderator.clone()))
+            .is_some_and(|grant| grant.role == ModeratorRole::Admin);
+        Self::require_admin(&env, &admin, role == ModeratorRole::Admin || current_is_admin)?;
+        if new_moderator == Self::get_owner_internal(&env)? {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        admin.require_auth();
+        
+        let categories = if role == ModeratorRole::Category { categories } else { Vec::new(&env) };
+        if role == ModeratorRole::Category && categories.is_empty() {
+            return Err(CommunityError::InvalidPolicy);
+        }
+        
+        let grant = ModeratorGrant { role, categories };
+        env.storage().instance().set(&DataKey::Moderator(new_moderator.clone()), &grant);
+        
+        env.events().publish(
+            (symbol_short!("add"), symbol_short!("mod")),
+            (admin, new_moderator, role)
+        );
+        
+        Ok(())
+    }
+    
+    /// Remove a moderator (admins only; only the owner removes admins)
+    pub fn remove_moderator(
+        env: Env,
+        admin: Address,
+        moderator_to_remove: Address,
+    ) -> Result<(), CommunityError> {
+        let current = Self::get_moderator(env.clone(), moderator_to_remove.clone())?;
+        Self::require_admin(&env, &admin, current.role == ModeratorRole::Admin)?;
+        if moderator_to_remove == Self::get_owner_internal(&env)? {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().remove(&DataKey::Moderator(moderator_to_remove.clone()));
+        
+        env.events().publish(
+            (symbol_short!("remove"), symbol_short!("mod")),
+            (admin, moderator_to_remove)
+        );
+        
+        Ok(())
+    }
+    
+    /// Get a moderator's role and categories
+    pub fn get_moderator(env: Env, moderator: Address) -> Result<ModeratorGrant, CommunityError> {
+        match env.storage().instance().get::<DataKey, ModeratorGrant>(&DataKey::Moderator(moderator)) {
+            Some(grant) => Ok(grant),
+            None => Err(CommunityError::NotModerator),
+        }
+    }
+    
+    /// Admins manage moderators; admin grants and removals are reserved for the owner
+    fn require_admin(env: &Env, admin: &Address, owner_only: bool) -> Result<(), CommunityError> {
+        if owner_only {
+            if *admin != Self::get_owner_internal(env)? {
+                return Err(CommunityError::Unauthorized);
+            }
+            return Ok(());
+        }
+        
+        match env.storage().instance().get::<DataKey, ModeratorGrant>(&DataKey::Moderator(admin.clone())) {
+            Some(grant) if grant.role == ModeratorRole::Admin => Ok(()),
+            _ => Err(CommunityError::Unauthorized),
+        }
+    }
+    
+    /// Check a moderator may act in `category`; `None` needs a global moderator or admin
+    fn check_scope(grant: &ModeratorGrant, category: Option<&Symbol>) -> Result<(), CommunityError> {
+        let allowed = match (grant.role, category) {
+            (ModeratorRole::Category, Some(category)) => grant.categories.contains(category),
+            (ModeratorRole::Category, None) => false,
+            _ => true,
+        };
+        if allowed { Ok(()) } else { Err(CommunityError::NotModerator) }
+    }
+    
+    /// Category of a post, or of the post a comment belongs to
+    fn target_category(env: &Env, target_id: &BytesN<32>, target: ReportTarget) -> Result<Symbol, CommunityError> {
+        let post_id = match target {
+            ReportTarget::Post => target_id.clone(),
+            ReportTarget::Comment => Self::get_comment(env.clone(), target_id.clone())?.post_id,
+        };
+        Ok(Self::get_post(env.clone(), post_id)?.category)
+    }
+    
+    fn log_moderation(env: &Env, target_id: &BytesN<32>, moderator: &Address, action: ModerationAction, reason: String) {
+        let mut log = env.storage().persistent().get::<DataKey, Vec<ModerationEntry>>(&DataKey::ModerationLog(target_id.clone())).unwrap_or(Vec::new(env));
+        log.push_back(ModerationEntry {
+            moderator: moderator.clone(),
+            action,
+            reason,
+            timestamp: env.ledger().timestamp(),
+        });
+        env.storage().persistent().set(&DataKey::ModerationLog(target_id.clone()), &log);
+    }
+    
+    /// Every moderation decision taken on a post or comment, oldest first
+    pub fn get_moderation_log(env: Env, target_id: BytesN<32>) -> Vec<ModerationEntry> {
+        env.storage().persistent().get::<DataKey, Vec<ModerationEntry>>(&DataKey::ModerationLog(target_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    /// Notify followers of the category and author, and anyone mentioned, that a post went live
+    fn announce_post(env: &Env, post: &Post) {
+        env.events().publish(
+            (symbol_short!("notify"), Symbol::new(env, "new_post_in_followed_category"), post.category.clone()),
+            post.id.clone()
+        );
+        if !post.anonymous {
+            env.events().publish(
+                (symbol_short!("notify"), Symbol::new(env, "new_post_by_followed_user"), post.author.clone()),
+                post.id.clone()
+            );
+        }
+        Self::notify_mentions(env, &post.author, &post.id, &post.mentions);
+    }
+    
+    fn notify_mentions(env: &Env, author: &Address, target_id: &BytesN<32>, mentions: &Vec<Address>) {
+        for user in mentions.iter() {
+            env.events().publish(
+                (symbol_short!("notify"), symbol_short!("mentioned"), user),
+                (author.clone(), target_id.clone())
+            );
+        }
+    }
+    
+    /// Follow another user
+    pub fn follow_user(
+        env: Env,
+        follower: Address,
+        user: Address,
+    ) -> Result<(), CommunityError> {
+        follower.require_auth();
+        
+        if follower == user {
+            return Err(CommunityError::InvalidPolicy);
+        }
+        
+        let follow_key = DataKey::Follow(follower.clone(), user.clone());
+        if env.storage().persistent().has(&follow_key) {
+            return Err(CommunityError::AlreadyFollowing);
+        }
+        let entry = FollowEntry {
+            following_pos: Self::follow_push(&env, FollowIndex::Following(follower.clone()), user.clone()),
+            follower_pos: Self::follow_push(&env, FollowIndex::Followers(user.clone()), follower.clone()),
+            timestamp: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&follow_key, &entry);
+        
+        env.events().publish(
+            (symbol_short!("notify"), symbol_short!("followed"), user),
+            follower
+        );
+        
+        Ok(())
+    }
+    
+    /// Stop following a user
+    pub fn unfollow_user(
+        env: Env,
+        follower: Address,
+        user: Address,
+    ) -> Result<(), CommunityError> {
+        follower.require_auth();
+        
+        let follow_key = DataKey::Follow(follower.clone(), user.clone());
+        let entry = match env.storage().persistent().get::<DataKey, FollowEntry>(&follow_key) {
+            Some(entry) => entry,
+            None => return Err(CommunityError::NotFollowing),
+        };
+        env.storage().persistent().remove(&follow_key);
+        
+        // The last address of each index fills the gap; point its follow at the new position
+        if let Some(moved) = Self::follow_remove(&env, FollowIndex::Following(follower.clone()), entry.following_pos) {
+            let moved_key = DataKey::Follow(follower.clone(), moved);
+            if let Some(mut moved_entry) = env.storage().persistent().get::<DataKey, FollowEntry>(&moved_key) {
+                moved_entry.following_pos = entry.following_pos;
+                env.storage().persistent().set(&moved_key, &moved_entry);
+            }
+        }
+        if let Some(moved) = Self::follow_remove(&env, FollowIndex::Followers(user.clone()), entry.follower_pos) {
+            let moved_key = DataKey::Follow(moved, user.clone());
+            if let Some(mut moved_entry) = env.storage().persistent().get::<DataKey, FollowEntry>(&moved_key) {
+                moved_entry.follower_pos = entry.follower_pos;
+                env.storage().persistent().set(&moved_key, &moved_entry);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Follow a category
+    pub fn follow_category(
+        env: Env,
+        follower: Address,
+        category: Symbol,
+    ) -> Result<(), CommunityError> {
+        follower.require_auth();
+        
+        Self::get_category(env.clone(), category.clone())?;
+        
+        let mut categories = env.storage().persistent().get::<DataKey, Vec<Symbol>>(&DataKey::FollowedCategories(follower.clone())).unwrap_or(Vec::new(&env));
+        if categories.contains(&category) {
+            return Err(CommunityError::AlreadyFollowing);
+        }
+        categories.push_back(category);
+        env.storage().persistent().set(&DataKey::FollowedCategories(follower), &categories);
+        
+        Ok(())
+    }
+    
+    /// Stop following a category
+    pub fn unfollow_category(
+        env: Env,
+        follower: Address,
+        category: Symbol,
+    ) -> Result<(), CommunityError> {
+        follower.require_auth();
+        
+        let mut categories = env.storage().persistent().get::<DataKey, Vec<Symbol>>(&DataKey::FollowedCategories(follower.clone())).unwrap_or(Vec::new(&env));
+        match categories.first_index_of(&category) {
+            Some(i) => { categories.remove(i); }
+            None => return Err(CommunityError::NotFollowing),
+        }
+        env.storage().persistent().set(&DataKey::FollowedCategories(follower), &categories);
+        
+        Ok(())
+    }
+    
+    /// Users `user` follows, starting at index `cursor`. Follow order is kept
+    /// until an unfollow, which moves the most recent follow into the gap.
+    pub fn list_following(env: Env, user: Address, cursor: u32, limit: u32) -> Vec<Address> {
+        Self::page_follows(&env, FollowIndex::Following(user), cursor, limit)
+    }
+    
+    /// Users following `user`, starting at index `cursor`; ordered like `list_following`
+    pub fn list_followers(env: Env, user: Address, cursor: u32, limit: u32) -> Vec<Address> {
+        Self::page_follows(&env, FollowIndex::Followers(user), cursor, limit)
+    }
+    
+    /// Whether `follower` follows `user`
+    pub fn is_following(env: Env, follower: Address, user: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Follow(follower, user))
+    }
+    
+    /// Categories `user` follows
+    pub fn list_followed_categories(env: Env, user: Address) -> Vec<Symbol> {
+        env.storage().persistent().get::<DataKey, Vec<Symbol>>(&DataKey::FollowedCategories(user)).unwrap_or(Vec::new(&env))
+    }
+    
+    fn follow_len(env: &Env, index: &FollowIndex) -> u32 {
+        env.storage().persistent().get::<DataKey, u32>(&DataKey::FollowLen(index.clone())).unwrap_or_default()
+    }
+    
+    /// Append an address to a follow index; returns its position
+    fn follow_push(env: &Env, index: FollowIndex, address: Address) -> u32 {
+        let len = Self::follow_len(env, &index);
+        let page_key = DataKey::FollowPage(index.clone(), len / INDEX_PAGE_SIZE);
+        let mut page = env.storage().persistent().get::<DataKey, Vec<Address>>(&page_key).unwrap_or(Vec::new(env));
+        page.push_back(address);
+        env.storage().persistent().set(&page_key, &page);
+        env.storage().persistent().set(&DataKey::FollowLen(index), &(len + 1));
+        len
+    }
+    
+    /// Remove the address at `pos` by moving the last address into it.
+    /// Returns the moved address, or `None` when `pos` was the last position.
+    fn follow_remove(env: &Env, index: FollowIndex, pos: u32) -> Option<Address> {
+        let len = Self::follow_len(env, &index);
+        if pos >= len {
+            return None;
+        }
+        let last = len - 1;
+        env.storage().persistent().set(&DataKey::FollowLen(index.clone()), &last);
+        
+        let last_page_key = DataKey::FollowPage(index.clone(), last / INDEX_PAGE_SIZE);
+        let mut last_page = env.storage().persistent().get::<DataKey, Vec<Address>>(&last_page_key).unwrap_or(Vec::new(env));
+        let moved = last_page.pop_back()?;
+        if last_page.is_empty() {
+            env.storage().persistent().remove(&last_page_key);
+        } else {
+            env.storage().persistent().set(&last_page_key, &last_page);
+        }
+        if pos == last {
+            return None;
+        }
+        
+        let page_key = DataKey::FollowPage(index, pos / INDEX_PAGE_SIZE);
+        let mut page = env.storage().persistent().get::<DataKey, Vec<Address>>(&page_key).unwrap_or(Vec::new(env));
+        page.set(pos % INDEX_PAGE_SIZE, moved.clone());
+        env.storage().persistent().set(&page_key, &page);
+        Some(moved)
+    }
+    
+    /// Addresses at positions `cursor..cursor + limit` of a follow index
+    fn page_follows(env: &Env, index: FollowIndex, cursor: u32, limit: u32) -> Vec<Address> {
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(Self::follow_len(env, &index));
+        let mut addresses = Vec::new(env);
+        let mut i = cursor;
+        while i < end {
+            let page_number = i / INDEX_PAGE_SIZE;
+            let page_end = ((page_number + 1) * INDEX_PAGE_SIZE).min(end);
+            let page = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::FollowPage(index.clone(), page_number)).unwrap_or(Vec::new(env));
+            for j in i..page_end {
+                if let Some(address) = page.get(j % INDEX_PAGE_SIZE) {
+                    addresses.push_back(address);
+                }
+            }
+            i = page_end;
+        }
+        addresses
+    }
+    
+    /// Grant an expert badge (owner only) once the owner has checked the
+    /// professional's license and specialty off-chain
+    pub fn grant_expert_badge(
+        env: Env,
+        owner: Address,
+        user: Address,
+        specialty: Symbol,
+    ) -> Result<(), CommunityError> {
+        let contract_owner = Self::get_owner_internal(&env)?;
+        if owner != contract_owner {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        owner.require_auth();
+        
+        let badge = ExpertBadge {
+            specialty,
+            granted_at: env.ledger().timestamp(),
+        };
+        Self::store_badge(&env, &user, badge);
+        
+        Ok(())
+    }
+    
+    fn store_badge(env: &Env, user: &Address, badge: ExpertBadge) {
+        env.storage().persistent().set(&DataKey::ExpertBadge(user.clone()), &badge);
+        
+        env.events().publish(
+            (symbol_short!("badge"), symbol_short!("granted")),
+            (user.clone(), badge.specialty)
+        );
+    }
+    
+    /// Revoke an expert badge (owner only)
+    pub fn revoke_expert_badge(
+        env: Env,
+        owner: Address,
+        user: Address,
+    ) -> Result<(), CommunityError> {
+        let contract_owner = Self::get_owner_internal(&env)?;
+        if owner != contract_owner {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        owner.require_auth();
+        
+        if !env.storage().persistent().has(&DataKey::ExpertBadge(user.clone())) {
+            return Err(CommunityError::NotExpert);
+        }
+        env.storage().persistent().remove(&DataKey::ExpertBadge(user.clone()));
+        
+        env.events().publish(
+            (symbol_short!("badge"), symbol_short!("revoked")),
+            user
+        );
+        
+        Ok(())
+    }
+    
+    /// Get a user's expert badge
+    pub fn get_expert_badge(env: Env, user: Address) -> Result<ExpertBadge, CommunityError> {
+        match env.storage().persistent().get::<DataKey, ExpertBadge>(&DataKey::ExpertBadge(user)) {
+            Some(badge) => Ok(badge),
+            None => Err(CommunityError::NotExpert),
+        }
+    }
+    
+    /// Mark a comment on a question as a verified answer (experts only, not on their own comments)
+    pub fn mark_verified_answer(
+        env: Env,
+        expert: Address,
+        comment_id: BytesN<32>,
+    ) -> Result<(), CommunityError> {
+        expert.require_auth();
+        
+        Self::get_expert_badge(env.clone(), expert.clone())?;
+        
+        let mut comment = Self::get_comment(env.clone(), comment_id.clone())?;
+        if comment.is_removed {
+            return Err(CommunityError::CommentRemoved);
+        }
+        if comment.author == expert || comment.verified_by.contains(&expert) {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        let mut post = Self::get_post(env.clone(), comment.post_id.clone())?;
+        let is_question = Self::get_category(env.clone(), post.category.clone())
+            .map(|rules| rules.questions)
+            .unwrap_or(false);
+        if !is_question {
+            return Err(CommunityError::NotAQuestion);
+        }
+        
+        comment.verified_by.push_back(expert.clone());
+        env.storage().persistent().set(&DataKey::Comment(comment_id.clone()), &comment);
+        
+        // Count each answer once however many experts verify it
+        if comment.verified_by.len() == 1 {
+            post.verified_answers += 1;
+            env.storage().persistent().set(&DataKey::Post(post.id.clone()), &post);
+        }
+        
+        env.events().publish(
+            (symbol_short!("verify"), symbol_short!("answer")),
+            (expert, post.id, comment_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Approved questions in a category with no verified answer yet, newest first.
+    /// Each call inspects at most `limit` posts, so a page may come back short.
+    pub fn list_unanswered_questions(env: Env, category: Symbol, cursor: u32, limit: u32) -> Result<PostPage, CommunityError> {
+        if !Self::get_category(env.clone(), category.clone())?.questions {
+            return Err(CommunityError::NotAQuestion);
+        }
+        
+        let (ids, next_cursor) = Self::index_newest(&env, &IndexKey::ApprovedInCategory(category), cursor, limit);
+        
+        let mut posts = Vec::new(&env);
+        for post_id in ids.iter() {
+            if let Some(post) = Self::feed_post(&env, post_id) {
+                if post.verified_answers == 0 {
+                    posts.push_back(post);
+                }
+            }
+        }
+        
+        Ok(PostPage { posts, next_cursor })
+    }
+    
+    /// Vote on a post
+    pub fn vote(
+        env: Env,
+        user: Address,
+        post_id: BytesN<32>,
+        vote_type: VoteType,
+    ) -> Result<(), CommunityError> {
+        user.require_auth();
+        
+        let post = env.storage().persistent().get::<DataKey, Post>(&DataKey::Post(post_id.clone()));
+        let mut post = match post {
+            Some(post) => post,
+            None => return Err(CommunityError::PostNotFound),
+        };
+        
+        if post.status != PostStatus::Approved || post.is_removed || post.is_hidden {
+            return Err(CommunityError::PostNotApproved);
+        }
+        if post.author == user {
+            return Err(CommunityError::SelfVote);
+        }
+        
+        let policy = Self::get_vote_policy(env.clone());
+        Self::check_voter(&env, &user, &policy)?;
+        
+        // Anonymous posts are authored by the contract, which earns no reputation
+        let author = if post.anonymous { None } else { Some(post.author.clone()) };
+        let vote_key = DataKey::Vote(post_id.clone(), user.clone());
+        
+        // Undo any previous vote with the weight it was cast at
+        let previous_vote = env.storage().persistent().get::<DataKey, CastVote>(&vote_key);
+        if let Some(prev) = previous_vote.clone() {
+            Self::apply_vote(&env, &mut post, author.as_ref(), prev.vote_type, -prev.weight);
+            env.storage().persistent().remove(&vote_key);
+        }
+        
+        // Voting the same way again cancels the vote
+        let weight = match previous_vote {
+            Some(prev) if prev.vote_type == vote_type => 0,
+            _ => {
+                let weight = Self::vote_weight(&env, &user, &policy);
+                Self::apply_vote(&env, &mut post, author.as_ref(), vote_type, weight);
+                env.storage().persistent().set(&vote_key, &CastVote { vote_type, weight });
+                weight
+            }
+        };
+        
+        env.storage().persistent().set(&DataKey::Post(post_id.clone()), &post);
+        env.events().publish(
+            (symbol_short!("vote"), symbol_short!("cast")),
+            (user, post_id, vote_type, weight)
+        );
+        Ok(())
+    }
+    
+    /// Only registered accounts past the minimum age may vote
+    fn check_voter(env: &Env, user: &Address, policy: &VotePolicy) -> Result<(), CommunityError> {
+        let registry = Self::check_registered(env, user)?;
+        
+        if policy.min_account_age_ledgers > 0 {
+            let registered_at = registry.get_registered_at(user);
+            if env.ledger().sequence().saturating_sub(registered_at) < policy.min_account_age_ledgers {
+                return Err(CommunityError::AccountTooNew);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Only accounts known to the registry may act; returns the registry client
+    fn check_registered<'a>(env: &'a Env, user: &Address) -> Result<RegistryClient<'a>, CommunityError> {
+        let registry = env.storage().instance().get::<DataKey, Address>(&DataKey::RegistryContract);
+        let registry = match registry {
+            Some(registry) => RegistryClient::new(env, &registry),
+            None => return Err(CommunityError::NotInitialized),
+        };
+        
+        if !registry.is_registered(user) {
+            return Err(CommunityError::NotRegistered);
+        }
+        
+        Ok(registry)
+    }
+    
+    /// Add a weighted vote to a post's tally and its author's reputation; negative weights undo one
+    fn apply_vote(env: &Env, post: &mut Post, author: Option<&Address>, vote_type: VoteType, weight: i128) {
+        match vote_type {
+            VoteType::Upvote => post.upvotes += weight,
+            VoteType::Downvote => post.downvotes += weight,
+        }
+        
+        if let Some(author) = author {
+            let delta = if vote_type == VoteType::Upvote { weight } else { -weight };
+            Self::adjust_reputation(env, author, delta);
+        }
+    }
+    
+    /// Weight of a vote: 1 plus one per `reputation_per_weight` points, capped at `max_vote_weight`
+    fn vote_weight(env: &Env, user: &Address, policy: &VotePolicy) -> i128 {
+        let reputation = Self::get_reputation(env.clone(), user.clone());
+        (1 + reputation / policy.reputation_per_weight).min(policy.max_vote_weight)
+    }
+    
+    /// Apply pending decay, then add `delta` to a user's reputation (never below zero)
+    fn adjust_reputation(env: &Env, user: &Address, delta: i128) {
+        let score = Self::get_reputation(env.clone(), user.clone());
+        let reputation = Reputation {
+            score: (score + delta).max(0),
+            updated_at: env.ledger().sequence(),
+        };
+        env.storage().persistent().set(&DataKey::Reputation(user.clone()), &reputation);
+        
+        env.events().publish(
+            (symbol_short!("rep"), user.clone()),
+            reputation.score
+        );
+    }
+    
+    /// Get a user's reputation, decayed for the time since it last changed
+    pub fn get_reputation(env: Env, user: Address) -> i128 {
+        let reputation = env.storage().persistent().get::<DataKey, Reputation>(&DataKey::Reputation(user));
+        let reputation = match reputation {
+            Some(reputation) => reputation,
+            None => return 0,
+        };
+        
+        let policy = Self::get_vote_policy(env.clone());
+        if policy.decay_period_ledgers == 0 {
+            return reputation.score;
+        }
+        
+        // Halve once per full idle period
+        let halvings = env.ledger().sequence().saturating_sub(reputation.updated_at) / policy.decay_period_ledgers;
+        reputation.score.checked_shr(halvings).unwrap_or(0)
+    }
+    
+    /// Configure voter eligibility, vote weighting and reputation decay (owner only)
+    pub fn set_vote_policy(
+        env: Env,
+        owner: Address,
+        policy: VotePolicy,
+    ) -> Result<(), CommunityError> {
+        let contract_owner = Self::get_owner_internal(&env)?;
+        if owner != contract_owner {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        owner.require_auth();
+        
+        if policy.reputation_per_weight <= 0 || policy.max_vote_weight < 1 {
+            return Err(CommunityError::InvalidPolicy);
+        }
+        env.storage().instance().set(&DataKey::VotePolicy, &policy);
+        
+        Ok(())
+    }
+    
+    /// Get the current vote policy
+    pub fn get_vote_policy(env: Env) -> VotePolicy {
+        env.storage().instance().get::<DataKey, VotePolicy>(&DataKey::VotePolicy).unwrap_or(VotePolicy::default_policy())
+    }
+    
+    /// Get a post by ID
+    pub fn get_post(
+        env: Env,
+        post_id: BytesN<32>,
+    ) -> Result<Post, CommunityError> {
+        let post = env.storage().persistent().get::<DataKey, Post>(&DataKey::Post(post_id));
+        match post {
+            Some(post) => Ok(post),
+            None => Err(CommunityError::PostNotFound),
+        }
+    }
+    
+    /// Get a comment by ID
+    pub fn get_comment(
+        env: Env,
+        comment_id: BytesN<32>,
+    ) -> Result<Comment, CommunityError> {
+        let comment = env.storage().persistent().get::<DataKey, Comment>(&DataKey::Comment(comment_id));
+        match comment {
+            Some(comment) => Ok(comment),
+            None => Err(CommunityError::CommentNotFound),
+        }
+    }
+    
+    /// Comment on an approved post, optionally as a reply to another comment.
+    /// The comment's reward points are taken back if it is later removed.
+    pub fn add_comment(
+        env: Env,
+        author: Address,
+        post_id: BytesN<32>,
+        content: String,
+        parent_comment_id: Option<BytesN<32>>,
+        mentions: Vec<Address>,
+    ) -> Result<BytesN<32>, CommunityError> {
+        author.require_auth();
+        
+        if mentions.len() > MAX_MENTIONS {
+            return Err(CommunityError::TooManyMentions);
+        }
+        
+        let comment_id = Self::generate_id(&env);
+        Self::insert_comment(&env, comment_id.clone(), author.clone(), post_id.clone(), content, parent_comment_id, None)?;
+        Self::award_comment(&env, &author, &comment_id);
+        Self::notify_mentions(&env, &author, &comment_id, &mentions);
+        
+        env.events().publish(
+            (symbol_short!("create"), symbol_short!("comment")),
+            (author, post_id, comment_id.clone())
+        );
+        
+        Ok(comment_id)
+    }
+    
+    /// Comment without linking the comment to an account; see `create_anonymous_post`.
+    /// `signature` is over `anonymous_comment_message`. Reusing the post's author key
+    /// keeps the same pseudonym within the thread.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_anonymous_comment(
+        env: Env,
+        post_id: BytesN<32>,
+        content: String,
+        parent_comment_id: Option<BytesN<32>>,
+        author_key: BytesN<32>,
+        identity_commitment: BytesN<32>,
+        identity_escrow: Bytes,
+        signature: BytesN<64>,
+    ) -> Result<BytesN<32>, CommunityError> {
+        // Traps on an invalid signature
+        let message = Self::anonymous_comment_message(env.clone(), post_id.clone(), content.clone(), parent_comment_id.clone(), author_key.clone(), identity_commitment.clone(), identity_escrow.clone());
+        Self::consume_author_key(&env, &author_key, &message, &signature);
+        
+        let comment_id = Self::generate_id(&env);
+        let pseudonym = Self::derive_pseudonym(&env, &author_key, &post_id);
+        
+        Self::insert_comment(&env, comment_id.clone(), env.current_contract_address(), post_id.clone(), content, parent_comment_id, Some(pseudonym.clone()))?;
+        Self::store_author_key(&env, &comment_id, author_key, identity_commitment, identity_escrow);
+        
+        env.events().publish(
+            (symbol_short!("create"), symbol_short!("anoncmt")),
+            (pseudonym, post_id, comment_id.clone())
+        );
+        
+        Ok(comment_id)
+    }
+    
+    fn insert_comment(
+        env: &Env,
+        comment_id: BytesN<32>,
+        author: Address,
+        post_id: BytesN<32>,
+        content: String,
+        parent_comment_id: Option<BytesN<32>>,
+        pseudonym: Option<BytesN<32>>,
+    ) -> Result<(), CommunityError> {
+        let mut post = Self::get_post(env.clone(), post_id.clone())?;
+        if post.status != PostStatus::Approved || post.is_removed || post.is_hidden {
+            return Err(CommunityError::PostNotApproved);
+        }
+        
+        // Attach replies to their parent, which must be a live comment on the same post
+        if let Some(parent_id) = parent_comment_id.clone() {
+            let mut parent = Self::get_comment(env.clone(), parent_id.clone())?;
+            if parent.post_id != post_id {
+                return Err(CommunityError::InvalidParentComment);
+            }
+            if parent.is_removed {
+                return Err(CommunityError::CommentRemoved);
+            }
+            parent.replies.push_back(comment_id.clone());
+            env.storage().persistent().set(&DataKey::Comment(parent_id), &parent);
+            
+            if !parent.anonymous && parent.author != author {
+                env.events().publish(
+                    (symbol_short!("notify"), Symbol::new(env, "reply_to_your_comment"), parent.author),
+                    (post_id.clone(), comment_id.clone())
+                );
+            }
+        }
+        
+        let comment = Comment {
+            id: comment_id.clone(),
+            post_id: post_id.clone(),
+            parent_id: parent_comment_id.unwrap_or(post_id.clone()),
+            author,
+            content,
+            timestamp: env.ledger().timestamp(),
+            edited_at: 0,
+            likes: 0,
+            replies: Vec::new(env),
+            is_removed: false,
+            is_hidden: false,
+            anonymous: pseudonym.is_some(),
+            pseudonym: pseudonym.unwrap_or(BytesN::from_array(env, &[0; 32])),
+            verified_by: Vec::new(env),
+        };
+        env.storage().persistent().set(&DataKey::Comment(comment_id.clone()), &comment);
+        
+        Self::index_push(env, IndexKey::Comments(post_id.clone()), comment_id);
+        post.comment_count += 1;
+        env.storage().persistent().set(&DataKey::Post(post_id), &post);
+        
+        Ok(())
+    }
+    
+    /// Edit a comment (author only)
+    pub fn edit_comment(
+        env: Env,
+        author: Address,
+        comment_id: BytesN<32>,
+        content: String,
+    ) -> Result<(), CommunityError> {
+        author.require_auth();
+        
+        let mut comment = Self::get_comment(env.clone(), comment_id.clone())?;
+        if comment.author != author {
+            return Err(CommunityError::Unauthorized);
+        }
+        if comment.is_removed {
+            return Err(CommunityError::CommentRemoved);
+        }
+        
+        comment.content = content;
+        comment.edited_at = env.ledger().timestamp();
+        env.storage().persistent().set(&DataKey::Comment(comment_id.clone()), &comment);
+        
+        env.events().publish(
+            (symbol_short!("edit"), symbol_short!("comment")),
+            (author, comment_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Soft-delete a comment (author or moderator). Replies stay in place.
+    /// `reason` is recorded in the moderation log when a moderator deletes.
+    pub fn delete_comment(
+        env: Env,
+        caller: Address,
+        comment_id: BytesN<32>,
+        reason: String,
+    ) -> Result<(), CommunityError> {
+        caller.require_auth();
+        
+        let mut comment = Self::get_comment(env.clone(), comment_id.clone())?;
+        let by_moderator = comment.author != caller;
+        if by_moderator {
+            let grant = match Self::get_moderator(env.clone(), caller.clone()) {
+                Ok(grant) => grant,
+                Err(_) => return Err(CommunityError::Unauthorized),
+            };
+            Self::check_scope(&grant, Some(&Self::target_category(&env, &comment_id, ReportTarget::Comment)?))?;
+        }
+        if comment.is_removed {
+            return Err(CommunityError::CommentRemoved);
+        }
+        
+        comment.is_removed = true;
+        env.storage().persistent().set(&DataKey::Comment(comment_id.clone()), &comment);
+        Self::settle_comment_reward(&env, &comment, false);
+        Self::dequeue_reports(&env, &comment_id);
+        if by_moderator {
+            Self::log_moderation(&env, &comment_id, &caller, ModerationAction::DeleteComment, reason);
+        }
+        
+        env.events().publish(
+            (symbol_short!("delete"), symbol_short!("comment")),
+            (caller, comment_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Like a comment, at most once per user
+    pub fn like_comment(
+        env: Env,
+        user: Address,
+        comment_id: BytesN<32>,
+    ) -> Result<(), CommunityError> {
+        user.require_auth();
+        
+        let mut comment = Self::get_comment(env.clone(), comment_id.clone())?;
+        if comment.is_removed {
+            return Err(CommunityError::CommentRemoved);
+        }
+        
+        let like_key = DataKey::CommentLike(comment_id.clone(), user.clone());
+        if env.storage().persistent().has(&like_key) {
+            return Err(CommunityError::AlreadyLiked);
+        }
+        env.storage().persistent().set(&like_key, &true);
+        
+        comment.likes += 1;
+        env.storage().persistent().set(&DataKey::Comment(comment_id.clone()), &comment);
+        
+        env.events().publish(
+            (symbol_short!("like"), symbol_short!("comment")),
+            (user, comment_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Message an anonymous author signs with their author key to perform
+    /// `action` on a post or comment. `content` is the new text for edits and
+    /// empty otherwise. Each accepted proof bumps a nonce, so proofs can't be replayed.
+    pub fn authorship_message(
+        env: Env,
+        target_id: BytesN<32>,
+        action: Symbol,
+        content: String,
+    ) -> Bytes {
+        let nonce = env.storage().persistent().get::<DataKey, u64>(&DataKey::AuthorProofNonce(target_id.clone())).unwrap_or_default();
+        (env.current_contract_address(), target_id, action, nonce, content).to_xdr(&env)
+    }
+    
+    /// Edit an anonymous comment with a proof of authorship
+    pub fn edit_anonymous_comment(
+        env: Env,
+        comment_id: BytesN<32>,
+        content: String,
+        signature: BytesN<64>,
+    ) -> Result<(), CommunityError> {
+        let mut comment = Self::get_comment(env.clone(), comment_id.clone())?;
+        if comment.is_removed {
+            return Err(CommunityError::CommentRemoved);
+        }
+        Self::verify_authorship(&env, &comment_id, symbol_short!("edit"), content.clone(), &signature)?;
+        
+        comment.content = content;
+        comment.edited_at = env.ledger().timestamp();
+        env.storage().persistent().set(&DataKey::Comment(comment_id.clone()), &comment);
+        
+        env.events().publish(
+            (symbol_short!("edit"), symbol_short!("comment")),
+            (comment.pseudonym, comment_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Soft-delete an anonymous comment with a proof of authorship
+    pub fn delete_anonymous_comment(
+        env: Env,
+        comment_id: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), CommunityError> {
+        let mut comment = Self::get_comment(env.clone(), comment_id.clone())?;
+        if comment.is_removed {
+            return Err(CommunityError::CommentRemoved);
+        }
+        Self::verify_authorship(&env, &comment_id, symbol_short!("delete"), String::from_str(&env, ""), &signature)?;
+        
+        comment.is_removed = true;
+        env.storage().persistent().set(&DataKey::Comment(comment_id.clone()), &comment);
+        Self::dequeue_reports(&env, &comment_id);
+        
+        env.events().publish(
+            (symbol_short!("delete"), symbol_short!("comment")),
+            (comment.pseudonym, comment_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Remove an anonymous post with a proof of authorship
+    pub fn delete_anonymous_post(
+        env: Env,
+        post_id: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), CommunityError> {
+        let mut post = Self::get_post(env.clone(), post_id.clone())?;
+        if post.is_removed {
+            return Err(CommunityError::PostNotFound);
+        }
+        Self::verify_authorship(&env, &post_id, symbol_short!("delete"), String::from_str(&env, ""), &signature)?;
+        
+        post.is_removed = true;
+        env.storage().persistent().set(&DataKey::Post(post_id.clone()), &post);
+        Self::dequeue_reports(&env, &post_id);
+        
+        env.events().publish(
+            (symbol_short!("delete"), symbol_short!("post")),
+            (post.pseudonym, post_id)
+        );
+        
+        Ok(())
+    }
+    
+    /// Message an anonymous author signs with `author_key` to create a post.
+    /// Each accepted signature bumps the key's nonce, so requests can't be replayed.
+    pub fn anonymous_post_message(
+        env: Env,
+        title: String,
+        content: String,
+        category: Symbol,
+        author_key: BytesN<32>,
+        identity_commitment: BytesN<32>,
+        identity_escrow: Bytes,
+    ) -> Bytes {
+        let nonce = env.storage().persistent().get::<DataKey, u64>(&DataKey::AuthorKeyNonce(author_key.clone())).unwrap_or_default();
+        (env.current_contract_address(), symbol_short!("post"), author_key, nonce, (title, content, category), identity_commitment, identity_escrow).to_xdr(&env)
+    }
+    
+    /// Message an anonymous author signs with `author_key` to comment; see `anonymous_post_message`
+    pub fn anonymous_comment_message(
+        env: Env,
+        post_id: BytesN<32>,
+        content: String,
+        parent_comment_id: Option<BytesN<32>>,
+        author_key: BytesN<32>,
+        identity_commitment: BytesN<32>,
+        identity_escrow: Bytes,
+    ) -> Bytes {
+        let nonce = env.storage().persistent().get::<DataKey, u64>(&DataKey::AuthorKeyNonce(author_key.clone())).unwrap_or_default();
+        (env.current_contract_address(), symbol_short!("comment"), author_key, nonce, (post_id, content, parent_comment_id), identity_commitment, identity_escrow).to_xdr(&env)
+    }
+    
+    /// Identity encrypted to the owner behind an anonymous post or comment
+    pub fn get_identity_escrow(env: Env, target_id: BytesN<32>) -> Result<Bytes, CommunityError> {
+        match env.storage().persistent().get::<DataKey, Bytes>(&DataKey::IdentityEscrow(target_id)) {
+            Some(escrow) => Ok(escrow),
+            None => Err(CommunityError::NotAnonymous),
+        }
+    }
+    
+    /// Escalation path: confirm that `author` wrote an anonymous post or comment
+    /// by opening its identity commitment with the author and salt decrypted from
+    /// `get_identity_escrow` (owner only). Moderators otherwise only ever see the pseudonym.
+    pub fn reveal_anonymous_author(
+        env: Env,
+        owner: Address,
+        target_id: BytesN<32>,
+        reason: Symbol,
+        author: Address,
+        identity_salt: BytesN<32>,
+    ) -> Result<(), CommunityError> {
+        let contract_owner = Self::get_owner_internal(&env)?;
+        if owner != contract_owner {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        owner.require_auth();
+        
+        let commitment = env.storage().persistent().get::<DataKey, BytesN<32>>(&DataKey::IdentityCommitment(target_id.clone()));
+        let commitment = match commitment {
+            Some(commitment) => commitment,
+            None => return Err(CommunityError::NotAnonymous),
+        };
+        
+        if Self::identity_commitment(&env, &author, &identity_salt) != commitment {
+            return Err(CommunityError::IdentityMismatch);
+        }
+        
+        env.events().publish(
+            (symbol_short!("escalate"), symbol_short!("reveal")),
+            (owner, target_id, reason, author)
+        );
+        
+        Ok(())
+    }
+    
+    /// Pseudonym shown for an author key within a post's thread
+    fn derive_pseudonym(env: &Env, author_key: &BytesN<32>, post_id: &BytesN<32>) -> BytesN<32> {
+        let mut bytes_to_hash = Bytes::from_array(env, &author_key.to_array());
+        bytes_to_hash.extend_from_array(&post_id.to_array());
+        env.crypto().sha256(&bytes_to_hash)
+    }
+    
+    /// Hash binding an anonymous author to their salt
+    fn identity_commitment(env: &Env, author: &Address, identity_salt: &BytesN<32>) -> BytesN<32> {
+        let mut bytes_to_hash = author.clone().to_xdr(env);
+        bytes_to_hash.extend_from_array(&identity_salt.to_array());
+        env.crypto().sha256(&bytes_to_hash)
+    }
+    
+    fn store_author_key(env: &Env, target_id: &BytesN<32>, author_key: BytesN<32>, identity_commitment: BytesN<32>, identity_escrow: Bytes) {
+        env.storage().persistent().set(&DataKey::AuthorKey(target_id.clone()), &author_key);
+        env.storage().persistent().set(&DataKey::IdentityCommitment(target_id.clone()), &identity_commitment);
+        env.storage().persistent().set(&DataKey::IdentityEscrow(target_id.clone()), &identity_escrow);
+    }
+    
+    /// Check a creation request signed by an author key and consume the key's nonce
+    fn consume_author_key(env: &Env, author_key: &BytesN<32>, message: &Bytes, signature: &BytesN<64>) {
+        env.crypto().ed25519_verify(author_key, message, signature);
+        
+        let nonce = env.storage().persistent().get::<DataKey, u64>(&DataKey::AuthorKeyNonce(author_key.clone())).unwrap_or_default();
+        env.storage().persistent().set(&DataKey::AuthorKeyNonce(author_key.clone()), &(nonce + 1));
+    }
+    
+    /// Check an anonymous author's signature and consume the proof nonce
+    fn verify_authorship(
+        env: &Env,
+        target_id: &BytesN<32>,
+        action: Symbol,
+        content: String,
+        signature: &BytesN<64>,
+    ) -> Result<(), CommunityError> {
+        let author_key = env.storage().persistent().get::<DataKey, BytesN<32>>(&DataKey::AuthorKey(target_id.clone()));
+        let author_key = match author_key {
+            Some(key) => key,
+            None => return Err(CommunityError::NotAnonymous),
+        };
+        
+        // Traps on an invalid signature
+        let message = Self::authorship_message(env.clone(), target_id.clone(), action, content);
+        env.crypto().ed25519_verify(&author_key, &message, signature);
+        
+        let nonce = env.storage().persistent().get::<DataKey, u64>(&DataKey::AuthorProofNonce(target_id.clone())).unwrap_or_default();
+        env.storage().persistent().set(&DataKey::AuthorProofNonce(target_id.clone()), &(nonce + 1));
+        
+        Ok(())
+    }
+    
+    /// List a post's comments in creation order, starting at index `cursor`.
+    /// Replies are included; use `parent_id` to rebuild threads. Comments hidden
+    /// by reports are skipped, so a page may hold fewer than `limit` entries.
+    pub fn list_comments(
+        env: Env,
+        post_id: BytesN<32>,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<Vec<Comment>, CommunityError> {
+        let post = Self::get_post(env.clone(), post_id)?;
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(post.comment_count);
+        
+        let mut comments = Vec::new(&env);
+        for comment_id in Self::index_range(&env, &IndexKey::Comments(post.id), cursor, end).iter() {
+            if let Some(comment) = env.storage().persistent().get::<DataKey, Comment>(&DataKey::Comment(comment_id)) {
+                if !comment.is_hidden {
+                    comments.push_back(comment);
+                }
+            }
+        }
+        
+        Ok(comments)
+    }
+    
+    /// Add a post to the approved feeds the first time it is approved. Entries are
+    /// never removed; feeds skip posts that are no longer visible when they are read.
+    fn index_approved(env: &Env, post: &Post) {
+        Self::index_push(env, IndexKey::Approved, post.id.clone());
+        Self::index_push(env, IndexKey::ApprovedInCategory(post.category.clone()), post.id.clone());
+    }
+    
+    fn is_visible(post: &Post) -> bool {
+        post.status == PostStatus::Approved && !post.is_removed && !post.is_hidden
+    }
+    
+    fn index_len(env: &Env, index: &IndexKey) -> u32 {
+        env.storage().persistent().get::<DataKey, u32>(&DataKey::IndexLen(index.clone())).unwrap_or_default()
+    }
+    
+    /// Append an ID to a paged index
+    fn index_push(env: &Env, index: IndexKey, id: BytesN<32>) {
+        let len = Self::index_len(env, &index);
+        let page_key = DataKey::IndexPage(index.clone(), len / INDEX_PAGE_SIZE);
+        let mut page = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&page_key).unwrap_or(Vec::new(env));
+        page.push_back(id);
+        env.storage().persistent().set(&page_key, &page);
+        env.storage().persistent().set(&DataKey::IndexLen(index), &(len + 1));
+    }
+    
+    /// IDs at positions `start..end` of a paged index, loading only the pages they span
+    fn index_range(env: &Env, index: &IndexKey, start: u32, end: u32) -> Vec<BytesN<32>> {
+        let mut ids = Vec::new(env);
+        let mut i = start;
+        while i < end {
+            let page_number = i / INDEX_PAGE_SIZE;
+            let page_end = ((page_number + 1) * INDEX_PAGE_SIZE).min(end);
+            let page = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&DataKey::IndexPage(index.clone(), page_number)).unwrap_or(Vec::new(env));
+            for j in i..page_end {
+                if let Some(id) = page.get(j % INDEX_PAGE_SIZE) {
+                    ids.push_back(id);
+                }
+            }
+            i = page_end;
+        }
+        ids
+    }
+    
+    /// Append an ID to a queue index, recording its position so it can be removed in place
+    fn queue_push(env: &Env, index: IndexKey, id: BytesN<32>) {
+        let pos = Self::index_len(env, &index);
+        env.storage().persistent().set(&DataKey::QueuePos(index.clone(), id.clone()), &pos);
+        Self::index_push(env, index, id);
+    }
+    
+    /// Remove an ID from a queue index by moving the last ID into its position
+    fn queue_remove(env: &Env, index: IndexKey, id: &BytesN<32>) {
+        let pos_key = DataKey::QueuePos(index.clone(), id.clone());
+        let pos = match env.storage().persistent().get::<DataKey, u32>(&pos_key) {
+            Some(pos) => pos,
+            None => return,
+        };
+        env.storage().persistent().remove(&pos_key);
+        
+        let last = Self::index_len(env, &index) - 1;
+        env.storage().persistent().set(&DataKey::IndexLen(index.clone()), &last);
+        
+        let last_page_key = DataKey::IndexPage(index.clone(), last / INDEX_PAGE_SIZE);
+        let mut last_page = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&last_page_key).unwrap_or(Vec::new(env));
+        let moved = match last_page.pop_back() {
+            Some(moved) => moved,
+            None => return,
+        };
+        if last_page.is_empty() {
+            env.storage().persistent().remove(&last_page_key);
+        } else {
+            env.storage().persistent().set(&last_page_key, &last_page);
+        }
+        if pos == last {
+            return;
+        }
+        
+        let page_key = DataKey::IndexPage(index.clone(), pos / INDEX_PAGE_SIZE);
+        let mut page = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&page_key).unwrap_or(Vec::new(env));
+        page.set(pos % INDEX_PAGE_SIZE, moved.clone());
+        env.storage().persistent().set(&page_key, &page);
+        env.storage().persistent().set(&DataKey::QueuePos(index, moved), &pos);
+    }
+    
+    /// Up to `limit` IDs from a paged index, newest first, skipping the `cursor` newest.
+    /// Returns them with the cursor for the next page (0 when exhausted).
+    fn index_newest(env: &Env, index: &IndexKey, cursor: u32, limit: u32) -> (Vec<BytesN<32>>, u32) {
+        let len = Self::index_len(env, index);
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+        if cursor >= end {
+            return (Vec::new(env), 0);
+        }
+        
+        let oldest_first = Self::index_range(env, index, len - end, len - cursor);
+        let mut ids = Vec::new(env);
+        for id in oldest_first.iter().rev() {
+            ids.push_back(id);
+        }
+        
+        let next_cursor = if end < len { end } else { 0 };
+        (ids, next_cursor)
+    }
+    
+    /// Page through an approved feed index. `Newest` walks the whole index; `Top`
+    /// and `Trending` only rank the `RANKING_WINDOW` most recently approved posts.
+    fn page_feed(env: &Env, index: IndexKey, sort: FeedSort, cursor: u32, limit: u32) -> PostPage {
+        let mut posts = Vec::new(env);
+        
+        if sort == FeedSort::Newest {
+            let (ids, next_cursor) = Self::index_newest(env, &index, cursor, limit);
+            for post_id in ids.iter() {
+                if let Some(post) = Self::feed_post(env, post_id) {
+                    posts.push_back(post);
+                }
+            }
+            return PostPage { posts, next_cursor };
+        }
+        
+        // Insertion sort newest first, so equal scores keep the newer post ahead
+        let len = Self::index_len(env, &index);
+        let window = Self::index_range(env, &index, len - len.min(RANKING_WINDOW), len);
+        let mut ranked: Vec<(i128, Post)> = Vec::new(env);
+        for post_id in window.iter().rev() {
+            let post = match Self::feed_post(env, post_id) {
+                Some(post) => post,
+                None => continue,
+            };
+            let score = Self::feed_score(env, &post, sort);
+            let mut at = ranked.len();
+            while at > 0 && ranked.get_unchecked(at - 1).0 < score {
+                at -= 1;
+            }
+            ranked.insert(at, (score, post));
+        }
+        
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(ranked.len());
+        for i in cursor..end {
+            posts.push_back(ranked.get_unchecked(i).1);
+        }
+        let next_cursor = if end < ranked.len() { end } else { 0 };
+        PostPage { posts, next_cursor }
+    }
+    
+    /// Load a post for a feed, skipping posts that are no longer visible or are in archived categories
+    fn feed_post(env: &Env, post_id: BytesN<32>) -> Option<Post> {
+        let post = env.storage().persistent().get::<DataKey, Post>(&DataKey::Post(post_id))?;
+        if !Self::is_visible(&post) || Self::is_archived(env, &post.category) {
+            return None;
+        }
+        Some(post)
+    }
+    
+    fn is_archived(env: &Env, category: &Symbol) -> bool {
+        env.storage().instance().get::<DataKey, CategoryInfo>(&DataKey::CategoryInfo(category.clone()))
+            .is_some_and(|info| info.archived)
+    }
+    
+    /// Ranking score: net votes, divided by the squared age in hours (plus two) when trending
+    fn feed_score(env: &Env, post: &Post, sort: FeedSort) -> i128 {
+        let net = post.upvotes - post.downvotes;
+        if sort != FeedSort::Trending {
+            return net;
+        }
+        
+        let age_hours = (env.ledger().timestamp().saturating_sub(post.timestamp) / 3600) as i128 + 2;
+        net * 1_000_000 / (age_hours * age_hours)
+    }
+    
+    /// List approved, visible posts. `Newest` pages through every approved post; `Top`
+    /// and `Trending` rank only the `RANKING_WINDOW` most recently approved posts, so
+    /// older posts are reachable through `Newest` alone. Each `Newest` call inspects at
+    /// most `limit` posts, so a page may come back short when posts were hidden or removed.
+    pub fn list_approved_posts(env: Env, sort: FeedSort, cursor: u32, limit: u32) -> PostPage {
+        Self::page_feed(&env, IndexKey::Approved, sort, cursor, limit)
+    }
+    
+    /// List approved, visible posts in a category; see `list_approved_posts`
+    pub fn list_posts_by_category(env: Env, category: Symbol, sort: FeedSort, cursor: u32, limit: u32) -> PostPage {
+        Self::page_feed(&env, IndexKey::ApprovedInCategory(category), sort, cursor, limit)
+    }
+    
+    /// List a user's posts, oldest first, optionally only those with a given status.
+    /// Posts in archived categories are skipped like in the other feeds.
+    /// Each call inspects at most `limit` posts, so a filtered page may come back short.
+    pub fn list_posts_by_user(
+        env: Env,
+        user: Address,
+        status: Option<PostStatus>,
+        cursor: u32,
+        limit: u32,
+    ) -> PostPage {
+        let index = IndexKey::UserPosts(user);
+        let len = Self::index_len(&env, &index);
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+        
+        let mut posts = Vec::new(&env);
+        for post_id in Self::index_range(&env, &index, cursor, end).iter() {
+            if let Some(post) = env.storage().persistent().get::<DataKey, Post>(&DataKey::Post(post_id)) {
+                if !post.is_removed && !Self::is_archived(&env, &post.category) && status.map_or(true, |status| post.status == status) {
+                    posts.push_back(post);
+                }
+            }
+        }
+        
+        let next_cursor = if end < len { end } else { 0 };
+        PostPage { posts, next_cursor }
+    }
+    
+    /// Credit a user with the scheduled points for an action
+    fn award(env: &Env, user: &Address, action: RewardAction) {
+        let amount = Self::get_reward_rate(env.clone(), action);
+        if amount == 0 {
+            return;
+        }
+        
+        let new_reward = Self::record_reward(env, user, action, amount);
+        
+        env.events().publish(
+            (symbol_short!("reward"), symbol_short!("author")),
+            (user.clone(), new_reward)
+        );
+    }
+    
+    /// Reward a new comment, for at most MAX_REWARDED_COMMENTS_PER_DAY per author.
+    /// The points are remembered so removing the comment can take them back.
+    fn award_comment(env: &Env, author: &Address, comment_id: &BytesN<32>) {
+        let amount = Self::get_reward_rate(env.clone(), RewardAction::CommentAdded);
+        if amount == 0 {
+            return;
+        }
+        
+        let day = env.ledger().timestamp() / 86_400;
+        let day_key = DataKey::CommentRewardDay(author.clone());
+        let rewarded = match env.storage().persistent().get::<DataKey, (u64, u32)>(&day_key) {
+            Some((last_day, count)) if last_day == day => count,
+            _ => 0,
+        };
+        if rewarded >= MAX_REWARDED_COMMENTS_PER_DAY {
+            return;
+        }
+        env.storage().persistent().set(&day_key, &(day, rewarded + 1));
+        env.storage().persistent().set(&DataKey::CommentReward(comment_id.clone()), &amount);
+        
+        Self::award(env, author, RewardAction::CommentAdded);
+    }
+    
+    /// Take back a comment's points when it is removed, or pay them again when it is restored
+    fn settle_comment_reward(env: &Env, comment: &Comment, restored: bool) {
+        let amount = env.storage().persistent().get::<DataKey, i128>(&DataKey::CommentReward(comment.id.clone()));
+        let amount = match amount {
+            Some(amount) => amount,
+            None => return,
+        };
+        
+        let (action, amount) = if restored { (RewardAction::CommentAdded, amount) } else { (RewardAction::CommentRemoved, -amount) };
+        let new_reward = Self::record_reward(env, &comment.author, action, amount);
+        
+        env.events().publish(
+            (symbol_short!("reward"), symbol_short!("author")),
+            (comment.author.clone(), new_reward)
+        );
+    }
+    
+    /// Apply a change to a user's reward balance and log it; returns the new balance
+    fn record_reward(env: &Env, user: &Address, action: RewardAction, amount: i128) -> i128 {
+        let current_reward = env.storage().persistent().get::<DataKey, i128>(&DataKey::Reward(user.clone())).unwrap_or_default();
+        let new_reward = current_reward + amount;
+        env.storage().persistent().set(&DataKey::Reward(user.clone()), &new_reward);
+        
+        let mut history = env.storage().persistent().get::<DataKey, Vec<RewardEntry>>(&DataKey::RewardHistory(user.clone())).unwrap_or(Vec::new(env));
+        if history.len() >= REWARD_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(RewardEntry {
+            action,
+            amount,
+            balance: new_reward,
+            timestamp: env.ledger().timestamp(),
+        });
+        env.storage().persistent().set(&DataKey::RewardHistory(user.clone()), &history);
+        
+        new_reward
+    }
+    
+    /// Exchange reward points one-for-one for the configured token
+    pub fn redeem_rewards(
+        env: Env,
+        user: Address,
+        amount: i128,
+    ) -> Result<(), CommunityError> {
+        user.require_auth();
+        
+        if amount <= 0 {
+            return Err(CommunityError::InvalidAmount);
+        }
+        let reward_token = env.storage().instance().get::<DataKey, Address>(&DataKey::RewardToken);
+        let reward_token = match reward_token {
+            Some(reward_token) => reward_token,
+            None => return Err(CommunityError::RewardTokenNotSet),
+        };
+        if Self::get_user_rewards(env.clone(), user.clone())? < amount {
+            return Err(CommunityError::InsufficientRewards);
+        }
+        
+        Self::record_reward(&env, &user, RewardAction::Redemption, -amount);
+        token::Client::new(&env, &reward_token).transfer(&env.current_contract_address(), &user, &amount);
+        
+        env.events().publish(
+            (symbol_short!("reward"), symbol_short!("redeem")),
+            (user, amount)
+        );
+        
+        Ok(())
+    }
+    
+    /// Set the token that redeemed rewards are paid in (owner only). The contract
+    /// pays from its own balance, so it must be funded separately.
+    pub fn set_reward_token(
+        env: Env,
+        owner: Address,
+        reward_token: Address,
+    ) -> Result<(), CommunityError> {
+        let contract_owner = Self::get_owner_internal(&env)?;
+        if owner != contract_owner {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        owner.require_auth();
+        
+        env.storage().instance().set(&DataKey::RewardToken, &reward_token);
+        
+        Ok(())
+    }
+    
+    /// Set the points an action earns (owner only)
+    pub fn set_reward_rate(
+        env: Env,
+        owner: Address,
+        action: RewardAction,
+        amount: i128,
+    ) -> Result<(), CommunityError> {
+        let contract_owner = Self::get_owner_internal(&env)?;
+        if owner != contract_owner {
+            return Err(CommunityError::Unauthorized);
+        }
+        
+        owner.require_auth();
+        
+        if amount < 0 || action == RewardAction::Redemption || action == RewardAction::CommentRemoved {
+            return Err(CommunityError::InvalidPolicy);
+        }
+        env.storage().instance().set(&DataKey::RewardRate(action), &amount);
+        
+        env.events().publish(
+            (symbol_short!("reward"), symbol_short!("rate")),
+            (action, amount)
+        );
+        
+        Ok(())
+    }
+    
+    /// Get the points an action earns
+    pub fn get_reward_rate(env: Env, action: RewardAction) -> i128 {
+        env.storage().instance().get::<DataKey, i128>(&DataKey::RewardRate(action)).unwrap_or(action.default_rate())
+    }
+    
+    /// A user's reward history, newest first, starting at index `cursor`
+    pub fn get_reward_history(env: Env, user: Address, cursor: u32, limit: u32) -> Vec<RewardEntry> {
+        let history = env.storage().persistent().get::<DataKey, Vec<RewardEntry>>(&DataKey::RewardHistory(user)).unwrap_or(Vec::new(&env));
+        let len = history.len();
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+        
+        let mut result = Vec::new(&env);
+        for i in cursor..end {
+            result.push_back(history.get_unchecked(len - 1 - i));
+        }
+        
+        result
+    }
+    
+    /// Get user's reward balance
     pub fn get_user_rewards(env: Env, user: Address) -> Result<i128, CommunityError> {
-        let reward = env.storage().instance().get::<DataKey, i128>(&DataKey::Reward(user));
+        let reward = env.storage().persistent().get::<DataKey, i128>(&DataKey::Reward(user));
         match reward {
             Some(reward) => Ok(reward),
             None => Ok(0),
         }
     }
-    
-    /// Check if an address is a moderator
-    pub fn is_moderator(
-        env: &Env,
-        moderator_address: Address,
-    ) -> bool {
-        match env.storage().instance().get::<DataKey, bool>(&DataKey::Moderator(moderator_address)) {
-            Some(is_mod) => is_mod,
-            None => false,
+    
+    /// Check if an address is a moderator of any tier
+    pub fn is_moderator(
+        env: &Env,
+        moderator_address: Address,
+    ) -> bool {
+        env.storage().instance().has(&DataKey::Moderator(moderator_address))
+    }
+    
+    /// Get the contract owner
+    fn get_owner_internal(env: &Env) -> Result<Address, CommunityError> {
+        let owner = env.storage().instance().get::<DataKey, Address>(&DataKey::Owner);
+        match owner {
+            Some(addr) => Ok(addr),
+            None => Err(CommunityError::NotInitialized),
+        }
+    }
+}
+
+/// Unit tests for the community contract
+#[cfg(test)]
+mod test {
+    extern crate std;
+    
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Events, Ledger};
+    use soroban_sdk::{vec, IntoVal, TryFromVal};
+    use registry_contract::{RegistryContract, RegistryContractClient};
+
+    fn add_categories(client: &CommunityContractClient, owner: &Address) {
+        let env = &client.env;
+        for (symbol, requires_moderation) in [("edu", true), ("poll", true), ("question", true), ("story", true), ("support", true), ("chat", false)] {
+            client.create_category(owner, &CategoryInfo {
+                symbol: Symbol::new(env, symbol),
+                display_name: String::from_str(env, symbol),
+                description: String::from_str(env, ""),
+                requires_moderation,
+                edit_requires_review: requires_moderation,
+                min_reputation: 0,
+                questions: symbol == "question",
+                archived: false,
+            });
+        }
+    }
+
+    fn setup_registry(env: &Env) -> RegistryContractClient<'_> {
+        let registry_id = env.register_contract(None, RegistryContract);
+        let registry = RegistryContractClient::new(env, &registry_id);
+        registry.initialize(&Address::generate(env));
+        registry
+    }
+
+    #[test]
+    fn test_create_and_moderate_post() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = Address::generate(&env);
+        client.initialize(&owner, &registry);
+        add_categories(&client, &owner);
+
+        let author = Address::generate(&env);
+        let title = String::from_str(&env, "Test Post");
+        let content = String::from_str(&env, "This is a test post.");
+        let category = symbol_short!("edu");
+        
+        let post_id = client.create_post(&author, &title, &content, &category, &Vec::new(&env));
+        
+        let post = client.get_post(&post_id);
+        assert_eq!(post.status, PostStatus::Pending);
+        
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        
+        let post_after_mod = client.get_post(&post_id);
+        assert_eq!(post_after_mod.status, PostStatus::Approved);
+        
+        let reward = client.get_user_rewards(&author);
+        assert_eq!(reward, 10);
+    }
+    
+    #[test]
+    fn test_voting() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+        let author = Address::generate(&env);
+        let voter = Address::generate(&env);
+        registry.register_user(&voter);
+        
+        let title = String::from_str(&env, "VotingPost");
+        let content = String::from_str(&env, "Vote here!");
+        let category = symbol_short!("poll");
+        
+        let post_id = client.create_post(&author, &title, &content, &category, &Vec::new(&env));
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok")); 
+        
+        client.vote(&voter, &post_id, &VoteType::Upvote);
+        let post_after_upvote = client.get_post(&post_id);
+        assert_eq!(post_after_upvote.upvotes, 1);
+        
+        client.vote(&voter, &post_id, &VoteType::Downvote);
+        let post_after_downvote = client.get_post(&post_id);
+        assert_eq!(post_after_downvote.upvotes, 0);
+        assert_eq!(post_after_downvote.downvotes, 1);
+
+        client.vote(&voter, &post_id, &VoteType::Downvote);
+        let post_after_remove_vote = client.get_post(&post_id);
+        assert_eq!(post_after_remove_vote.downvotes, 0);
+    }
+
+    #[test]
+    fn test_vote_eligibility_and_weighting() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+        client.set_vote_policy(&owner, &VotePolicy {
+            min_account_age_ledgers: 100,
+            reputation_per_weight: 10,
+            max_vote_weight: 3,
+            decay_period_ledgers: 1_000,
+        });
+
+        let veteran = Address::generate(&env);
+        let newcomer = Address::generate(&env);
+        let stranger = Address::generate(&env);
+        registry.register_user(&veteran);
+
+        // The veteran earns reputation from two approved posts
+        for title in ["Ovulation", "Luteal phase"] {
+            let post_id = client.create_post(&veteran, &String::from_str(&env, title), &String::from_str(&env, "Notes"), &symbol_short!("edu"), &Vec::new(&env));
+            client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        }
+        assert_eq!(client.get_reputation(&veteran), 20);
+
+        let author = Address::generate(&env);
+        let post_id = client.create_post(&author, &String::from_str(&env, "Cycle apps"), &String::from_str(&env, "Compare"), &symbol_short!("edu"), &Vec::new(&env));
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        let res = client.try_vote(&author, &post_id, &VoteType::Upvote);
+        assert_eq!(res, Err(Ok(CommunityError::SelfVote)));
+
+        env.ledger().with_mut(|li| li.sequence_number = 150);
+        registry.register_user(&newcomer);
+        let res = client.try_vote(&stranger, &post_id, &VoteType::Upvote);
+        assert_eq!(res, Err(Ok(CommunityError::NotRegistered)));
+        let res = client.try_vote(&newcomer, &post_id, &VoteType::Upvote);
+        assert_eq!(res, Err(Ok(CommunityError::AccountTooNew)));
+
+        // 20 reputation gives a weight of 3, which the author earns as reputation
+        client.vote(&veteran, &post_id, &VoteType::Upvote);
+        assert_eq!(client.get_post(&post_id).upvotes, 3);
+        assert_eq!(client.get_reputation(&author), 13);
+        client.vote(&veteran, &post_id, &VoteType::Downvote);
+        let post = client.get_post(&post_id);
+        assert_eq!((post.upvotes, post.downvotes), (0, 3));
+        assert_eq!(client.get_reputation(&author), 7);
+
+        // Reputation halves after each idle decay period
+        env.ledger().with_mut(|li| li.sequence_number = 2_150);
+        assert_eq!(client.get_reputation(&veteran), 5);
+        assert_eq!(client.get_reputation(&author), 1);
+    }
+
+    #[test]
+    fn test_sorted_and_paginated_feeds() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+        let author = Address::generate(&env);
+        let voter = Address::generate(&env);
+        registry.register_user(&voter);
+
+        let content = String::from_str(&env, "Body");
+        let old = client.create_post(&author, &String::from_str(&env, "Old"), &content, &symbol_short!("edu"), &Vec::new(&env));
+        client.moderate_post(&owner, &old, &true, &String::from_str(&env, "ok"));
+        client.vote(&voter, &old, &VoteType::Upvote);
+        env.ledger().with_mut(|li| li.timestamp = 48 * 3600);
+        let pending = client.create_post(&author, &String::from_str(&env, "Pending"), &content, &symbol_short!("edu"), &Vec::new(&env));
+        let fresh = client.create_post(&author, &String::from_str(&env, "Fresh"), &content, &symbol_short!("edu"), &Vec::new(&env));
+        let other = client.create_post(&author, &String::from_str(&env, "Other"), &content, &symbol_short!("poll"), &Vec::new(&env));
+        client.moderate_post(&owner, &fresh, &true, &String::from_str(&env, "ok"));
+        client.moderate_post(&owner, &other, &true, &String::from_str(&env, "ok"));
+        client.vote(&voter, &other, &VoteType::Downvote);
+
+        let page = client.list_approved_posts(&FeedSort::Newest, &0, &2);
+        assert_eq!(page.posts.len(), 2);
+        assert_eq!(page.posts.get_unchecked(0).id, other);
+        assert_eq!(page.next_cursor, 2);
+        let page = client.list_approved_posts(&FeedSort::Newest, &page.next_cursor, &2);
+        assert_eq!(page.posts.get_unchecked(0).id, old);
+        assert_eq!(page.next_cursor, 0);
+
+        let top = client.list_approved_posts(&FeedSort::Top, &0, &10).posts;
+        assert_eq!(top.get_unchecked(0).id, old);
+        assert_eq!(top.get_unchecked(2).id, other);
+
+        // A fresh vote outweighs an older one once age is taken into account
+        client.vote(&voter, &fresh, &VoteType::Upvote);
+        let trending = client.list_posts_by_category(&symbol_short!("edu"), &FeedSort::Trending, &0, &10).posts;
+        assert_eq!(trending.len(), 2);
+        assert_eq!(trending.get_unchecked(0).id, fresh);
+
+        // Removal drops the post from every feed
+        client.remove_post(&owner, &fresh, &String::from_str(&env, "ok"));
+        assert_eq!(client.list_posts_by_category(&symbol_short!("edu"), &FeedSort::Newest, &0, &10).posts.len(), 1);
+        assert_eq!(client.list_approved_posts(&FeedSort::Newest, &0, &10).posts.len(), 2);
+
+        let pending_posts = client.list_posts_by_user(&author, &Some(PostStatus::Pending), &0, &10).posts;
+        assert_eq!(pending_posts.len(), 1);
+        assert_eq!(pending_posts.get_unchecked(0).id, pending);
+    }
+
+    #[test]
+    fn test_feed_pages_span_index_pages() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        client.initialize(&owner, &Address::generate(&env));
+        add_categories(&client, &owner);
+        let author = Address::generate(&env);
+
+        // "chat" needs no moderation, so every post is approved on creation
+        let total = INDEX_PAGE_SIZE + 6;
+        let mut ids = std::vec::Vec::new();
+        for _ in 0..total {
+            ids.push(client.create_post(&author, &String::from_str(&env, "Hi"), &String::from_str(&env, "Body"), &symbol_short!("chat"), &Vec::new(&env)));
+        }
+        client.delete_post(&author, &ids[INDEX_PAGE_SIZE as usize]);
+
+        // Newest first across the page boundary; the deleted post leaves a short page
+        let mut seen = std::vec::Vec::new();
+        let mut cursor = 0;
+        loop {
+            let page = client.list_approved_posts(&FeedSort::Newest, &cursor, &MAX_PAGE_SIZE);
+            seen.extend(page.posts.iter().map(|post| post.id));
+            if page.next_cursor == 0 {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        assert_eq!(seen.len() as u32, total - 1);
+        assert_eq!(seen[0], ids[ids.len() - 1]);
+        assert_eq!(seen[seen.len() - 1], ids[0]);
+
+        // Ranking only looks at the newest posts; the deleted post falls inside that window
+        let top = client.list_approved_posts(&FeedSort::Top, &0, &MAX_PAGE_SIZE).posts;
+        assert_eq!(top.len(), RANKING_WINDOW - 1);
+        assert!(top.iter().all(|post| post.id != ids[0]));
+
+        let by_user = client.list_posts_by_user(&author, &None, &(INDEX_PAGE_SIZE - 1), &3).posts;
+        assert_eq!(by_user.len(), 2);
+        assert_eq!(by_user.get_unchecked(1).id, ids[INDEX_PAGE_SIZE as usize + 1]);
+    }
+
+    #[test]
+    fn test_edit_and_delete_post() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+        let author = Address::generate(&env);
+        let voter = Address::generate(&env);
+        registry.register_user(&voter);
+
+        let title = String::from_str(&env, "Endometriosis");
+        let reviewed = client.create_post(&author, &title, &String::from_str(&env, "Teh symptoms"), &symbol_short!("edu"), &Vec::new(&env));
+        let casual = client.create_post(&author, &String::from_str(&env, "Hello"), &String::from_str(&env, "Hi all"), &symbol_short!("chat"), &Vec::new(&env));
+        client.moderate_post(&owner, &reviewed, &true, &String::from_str(&env, "ok"));
+        assert_eq!(client.get_post(&casual).status, PostStatus::Approved);
+        client.vote(&voter, &reviewed, &VoteType::Upvote);
+
+        let fixed = String::from_str(&env, "The symptoms");
+        let res = client.try_edit_post(&voter, &reviewed, &title, &fixed);
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+
+        env.ledger().with_mut(|li| li.timestamp = 500);
+        client.edit_post(&author, &reviewed, &title, &fixed);
+        let post = client.get_post(&reviewed);
+        assert_eq!(post.content, fixed);
+        assert_eq!(post.edited_at, 500);
+        assert_eq!(post.upvotes, 1);
+        assert_eq!(post.status, PostStatus::Pending);
+        let history = client.get_post_history(&reviewed);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.get_unchecked(0).content, String::from_str(&env, "Teh symptoms"));
+
+        // Re-approval does not reward the author twice
+        client.moderate_post(&owner, &reviewed, &true, &String::from_str(&env, "ok"));
+        assert_eq!(client.get_user_rewards(&author), 10);
+
+        client.edit_post(&author, &casual, &String::from_str(&env, "Hello!"), &String::from_str(&env, "Hi everyone"));
+        assert_eq!(client.get_post(&casual).status, PostStatus::Approved);
+
+        // Only the latest revisions are kept
+        for _ in 0..POST_HISTORY_LEN {
+            client.edit_post(&author, &casual, &String::from_str(&env, "Hello!"), &String::from_str(&env, "Hi again"));
         }
+        let history = client.get_post_history(&casual);
+        assert_eq!(history.len(), POST_HISTORY_LEN);
+        assert_eq!(history.get_unchecked(0).content, String::from_str(&env, "Hi everyone"));
+
+        client.delete_post(&author, &casual);
+        assert!(client.get_post(&casual).is_removed);
+        assert_eq!(client.list_approved_posts(&FeedSort::Newest, &0, &10).posts.len(), 1);
+        let res = client.try_delete_post(&author, &casual);
+        assert_eq!(res, Err(Ok(CommunityError::PostNotFound)));
     }
-    
-    /// Get the contract owner
-    fn get_owner_internal(env: &Env) -> Result<Address, CommunityError> {
-        let owner = env.storage().instance().get::<DataKey, Address>(&DataKey::Owner);
-        match owner {
-            Some(addr) => Ok(addr),
-            None => Err(CommunityError::NotInitialized),
+
+    #[test]
+    fn test_category_rules_and_archiving() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        client.initialize(&owner, &Address::generate(&env));
+        add_categories(&client, &owner);
+        let author = Address::generate(&env);
+
+        let experts = CategoryInfo {
+            symbol: symbol_short!("research"),
+            display_name: String::from_str(&env, "Research"),
+            description: String::from_str(&env, "Studies and papers"),
+            requires_moderation: true,
+            edit_requires_review: true,
+            min_reputation: 10,
+            questions: false,
+            archived: false,
+        };
+        let res = client.try_create_category(&author, &experts);
+        assert_eq!(res, Err(Ok(CommunityError::NotModerator)));
+        client.create_category(&owner, &experts);
+        let res = client.try_create_category(&owner, &experts);
+        assert_eq!(res, Err(Ok(CommunityError::CategoryExists)));
+        assert_eq!(client.list_categories().len(), 7);
+
+        let title = String::from_str(&env, "Title");
+        let content = String::from_str(&env, "Body");
+        let res = client.try_create_post(&author, &title, &content, &symbol_short!("Edu"), &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::CategoryNotFound)));
+        let res = client.try_create_post(&author, &title, &content, &symbol_short!("research"), &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::InsufficientReputation)));
+
+        let post_id = client.create_post(&author, &title, &content, &symbol_short!("edu"), &Vec::new(&env));
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        client.create_post(&author, &title, &content, &symbol_short!("research"), &Vec::new(&env));
+
+        // Archiving hides the category's posts from feeds but keeps them
+        client.archive_category(&owner, &symbol_short!("edu"), &true);
+        assert!(client.get_category(&symbol_short!("edu")).archived);
+        assert_eq!(client.list_approved_posts(&FeedSort::Newest, &0, &10).posts.len(), 0);
+        assert_eq!(client.list_posts_by_category(&symbol_short!("edu"), &FeedSort::Top, &0, &10).posts.len(), 0);
+        assert_eq!(client.list_posts_by_user(&author, &None, &0, &10).posts.len(), 1);
+        assert_eq!(client.get_post(&post_id).status, PostStatus::Approved);
+        let res = client.try_create_post(&author, &title, &content, &symbol_short!("edu"), &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::CategoryArchived)));
+
+        client.archive_category(&owner, &symbol_short!("edu"), &false);
+        assert_eq!(client.list_approved_posts(&FeedSort::Newest, &0, &10).posts.len(), 1);
+        assert_eq!(client.list_posts_by_user(&author, &None, &0, &10).posts.len(), 2);
+    }
+
+    #[test]
+    fn test_redeem_rewards_for_tokens() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        client.initialize(&owner, &Address::generate(&env));
+        add_categories(&client, &owner);
+        let author = Address::generate(&env);
+
+        let token_id = env.register_stellar_asset_contract(owner.clone());
+        soroban_sdk::token::StellarAssetClient::new(&env, &token_id).mint(&contract_id, &1_000);
+        let token = soroban_sdk::token::Client::new(&env, &token_id);
+
+        let res = client.try_redeem_rewards(&author, &5);
+        assert_eq!(res, Err(Ok(CommunityError::RewardTokenNotSet)));
+        client.set_reward_token(&owner, &token_id);
+        client.set_reward_rate(&owner, &RewardAction::CommentAdded, &2);
+        let res = client.try_set_reward_rate(&owner, &RewardAction::Redemption, &2);
+        assert_eq!(res, Err(Ok(CommunityError::InvalidPolicy)));
+
+        let post_id = client.create_post(&author, &String::from_str(&env, "Hydration"), &String::from_str(&env, "Tips"), &symbol_short!("edu"), &Vec::new(&env));
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        client.add_comment(&author, &post_id, &String::from_str(&env, "Also electrolytes"), &None, &Vec::new(&env));
+        assert_eq!(client.get_user_rewards(&author), 12);
+
+        let res = client.try_redeem_rewards(&author, &13);
+        assert_eq!(res, Err(Ok(CommunityError::InsufficientRewards)));
+        let res = client.try_redeem_rewards(&author, &0);
+        assert_eq!(res, Err(Ok(CommunityError::InvalidAmount)));
+        client.redeem_rewards(&author, &8);
+        assert_eq!(client.get_user_rewards(&author), 4);
+        assert_eq!(token.balance(&author), 8);
+        assert_eq!(token.balance(&contract_id), 992);
+
+        let history = client.get_reward_history(&author, &0, &10);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.get_unchecked(0).action, RewardAction::Redemption);
+        assert_eq!(history.get_unchecked(0).amount, -8);
+        assert_eq!(history.get_unchecked(2).action, RewardAction::PostApproved);
+
+        // Removing a comment takes its points back
+        let extra = client.add_comment(&author, &post_id, &String::from_str(&env, "And sleep"), &None, &Vec::new(&env));
+        assert_eq!(client.get_user_rewards(&author), 6);
+        client.delete_comment(&author, &extra, &String::from_str(&env, ""));
+        assert_eq!(client.get_user_rewards(&author), 4);
+        let history = client.get_reward_history(&author, &0, &1);
+        assert_eq!(history.get_unchecked(0).action, RewardAction::CommentRemoved);
+        assert_eq!(history.get_unchecked(0).amount, -2);
+
+        // Only a few comments a day earn points, deleted ones included
+        for _ in 0..MAX_REWARDED_COMMENTS_PER_DAY {
+            client.add_comment(&author, &post_id, &String::from_str(&env, "More tips"), &None, &Vec::new(&env));
         }
+        assert_eq!(client.get_user_rewards(&author), 4 + 2 * (MAX_REWARDED_COMMENTS_PER_DAY as i128 - 2));
+        env.ledger().with_mut(|li| li.timestamp += 86_400);
+        client.add_comment(&author, &post_id, &String::from_str(&env, "Next day"), &None, &Vec::new(&env));
+        assert_eq!(client.get_user_rewards(&author), 6 + 2 * (MAX_REWARDED_COMMENTS_PER_DAY as i128 - 2));
     }
-}
 
-/// Unit tests for the community contract
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::testutils::{Address as _, AuthorizedFunction, Events};
-    use soroban_sdk::{vec};
+    #[test]
+    fn test_moderator_tiers_and_log() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        client.initialize(&owner, &Address::generate(&env));
+        add_categories(&client, &owner);
+        let admin = Address::generate(&env);
+        let edu_mod = Address::generate(&env);
+        let author = Address::generate(&env);
+
+        client.add_moderator(&owner, &admin, &ModeratorRole::Admin, &Vec::new(&env));
+        let res = client.try_add_moderator(&admin, &author, &ModeratorRole::Admin, &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        let res = client.try_add_moderator(&admin, &edu_mod, &ModeratorRole::Category, &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::InvalidPolicy)));
+        client.add_moderator(&admin, &edu_mod, &ModeratorRole::Category, &vec![&env, symbol_short!("edu")]);
+        assert_eq!(client.get_moderator(&edu_mod).role, ModeratorRole::Category);
+
+        // Admins cannot demote other admins or touch the owner's grant
+        let other_admin = Address::generate(&env);
+        client.add_moderator(&owner, &other_admin, &ModeratorRole::Admin, &Vec::new(&env));
+        let res = client.try_add_moderator(&admin, &other_admin, &ModeratorRole::Global, &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        let res = client.try_add_moderator(&admin, &owner, &ModeratorRole::Category, &vec![&env, symbol_short!("edu")]);
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        let res = client.try_add_moderator(&owner, &owner, &ModeratorRole::Global, &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        client.add_moderator(&owner, &other_admin, &ModeratorRole::Global, &Vec::new(&env));
+        assert_eq!(client.get_moderator(&other_admin).role, ModeratorRole::Global);
+
+        let content = String::from_str(&env, "Body");
+        let edu_post = client.create_post(&author, &String::from_str(&env, "Edu"), &content, &symbol_short!("edu"), &Vec::new(&env));
+        let poll_post = client.create_post(&author, &String::from_str(&env, "Poll"), &content, &symbol_short!("poll"), &Vec::new(&env));
+
+        // Category moderators only act within their categories
+        let reason = String::from_str(&env, "Off-topic");
+        let res = client.try_moderate_post(&edu_mod, &poll_post, &true, &reason);
+        assert_eq!(res, Err(Ok(CommunityError::NotModerator)));
+        let res = client.try_archive_category(&edu_mod, &symbol_short!("edu"), &true);
+        assert_eq!(res, Err(Ok(CommunityError::NotModerator)));
+        client.moderate_post(&edu_mod, &edu_post, &true, &String::from_str(&env, "Accurate"));
+        client.remove_post(&edu_mod, &edu_post, &reason);
+
+        let log = client.get_moderation_log(&edu_post);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.get_unchecked(0).action, ModerationAction::Approve);
+        assert_eq!(log.get_unchecked(1), ModerationEntry {
+            moderator: edu_mod.clone(),
+            action: ModerationAction::Remove,
+            reason,
+            timestamp: env.ledger().timestamp(),
+        });
+
+        let res = client.try_remove_moderator(&admin, &owner);
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        client.remove_moderator(&admin, &edu_mod);
+        assert!(client.try_get_moderator(&edu_mod).is_err());
+    }
+
+    fn notifications(env: &Env) -> Vec<(Address, Vec<soroban_sdk::Val>, soroban_sdk::Val)> {
+        let mut result = Vec::new(env);
+        for event in env.events().all().iter() {
+            let topic = Symbol::try_from_val(env, &event.1.get_unchecked(0));
+            if topic == Ok(symbol_short!("notify")) {
+                result.push_back(event);
+            }
+        }
+        result
+    }
 
     #[test]
-    fn test_create_and_moderate_post() {
+    fn test_follows_mentions_and_notifications() {
         let env = Env::default();
         env.mock_all_auths();
         let contract_id = env.register_contract(None, CommunityContract);
         let client = CommunityContractClient::new(&env, &contract_id);
 
         let owner = Address::generate(&env);
-        let registry = Address::generate(&env);
-        client.initialize(&owner, &registry);
+        client.initialize(&owner, &Address::generate(&env));
+        add_categories(&client, &owner);
+        let author = Address::generate(&env);
+        let readers = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+
+        for reader in readers.iter() {
+            client.follow_user(reader, &author);
+        }
+        let res = client.try_follow_user(&readers[0], &author);
+        assert_eq!(res, Err(Ok(CommunityError::AlreadyFollowing)));
+        assert_eq!(client.list_followers(&author, &1, &5), vec![&env, readers[1].clone(), readers[2].clone()]);
+        assert_eq!(client.list_following(&readers[0], &0, &5), vec![&env, author.clone()]);
+        // Unfollowing moves the latest follower into the gap
+        client.unfollow_user(&readers[1], &author);
+        assert_eq!(client.list_followers(&author, &0, &5), vec![&env, readers[0].clone(), readers[2].clone()]);
+        assert!(!client.is_following(&readers[1], &author));
+        let res = client.try_unfollow_user(&readers[1], &author);
+        assert_eq!(res, Err(Ok(CommunityError::NotFollowing)));
+        client.unfollow_user(&readers[2], &author);
+        assert_eq!(client.list_followers(&author, &0, &5), vec![&env, readers[0].clone()]);
+        assert_eq!(client.list_following(&readers[2], &0, &5).len(), 0);
+        client.follow_user(&readers[2], &author);
+        assert!(client.is_following(&readers[2], &author));
+        assert_eq!(client.list_followers(&author, &0, &5).len(), 2);
+
+        client.follow_category(&readers[0], &symbol_short!("edu"));
+        let res = client.try_follow_category(&readers[0], &symbol_short!("nope"));
+        assert_eq!(res, Err(Ok(CommunityError::CategoryNotFound)));
+        assert_eq!(client.list_followed_categories(&readers[0]), vec![&env, symbol_short!("edu")]);
+
+        // Approval announces the post to category and author followers, and to mentioned users
+        let post_id = client.create_post(&author, &String::from_str(&env, "Cycle syncing"), &String::from_str(&env, "Thoughts?"), &symbol_short!("edu"), &vec![&env, readers[2].clone()]);
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        let notify = symbol_short!("notify");
+        // The first four notifications are the follows above
+        assert_eq!(notifications(&env).slice(4..), vec![
+            &env,
+            (contract_id.clone(), (notify.clone(), Symbol::new(&env, "new_post_in_followed_category"), symbol_short!("edu")).into_val(&env), post_id.clone().into_val(&env)),
+            (contract_id.clone(), (notify.clone(), Symbol::new(&env, "new_post_by_followed_user"), author.clone()).into_val(&env), post_id.clone().into_val(&env)),
+            (contract_id.clone(), (notify.clone(), symbol_short!("mentioned"), readers[2].clone()).into_val(&env), (author.clone(), post_id.clone()).into_val(&env)),
+        ]);
+
+        let comment_id = client.add_comment(&readers[0], &post_id, &String::from_str(&env, "Interesting"), &None, &Vec::new(&env));
+        let reply_id = client.add_comment(&author, &post_id, &String::from_str(&env, "Thanks"), &Some(comment_id), &Vec::new(&env));
+        assert_eq!(notifications(&env).slice(7..), vec![
+            &env,
+            (contract_id.clone(), (notify, Symbol::new(&env, "reply_to_your_comment"), readers[0].clone()).into_val(&env), (post_id, reply_id).into_val(&env)),
+        ]);
+    }
+
+    #[test]
+    fn test_expert_badges_and_verified_answers() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        client.initialize(&owner, &Address::generate(&env));
+        add_categories(&client, &owner);
+        let asker = Address::generate(&env);
+        let helper = Address::generate(&env);
+        let gynaecologist = Address::generate(&env);
+        let midwife = Address::generate(&env);
+
+        let res = client.try_grant_expert_badge(&midwife, &midwife, &symbol_short!("midwife"));
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        client.grant_expert_badge(&owner, &gynaecologist, &symbol_short!("gyn"));
+        client.grant_expert_badge(&owner, &midwife, &symbol_short!("midwife"));
+        assert_eq!(client.get_expert_badge(&midwife).specialty, symbol_short!("midwife"));
+
+        let content = String::from_str(&env, "Body");
+        let answered = client.create_post(&asker, &String::from_str(&env, "Spotting?"), &content, &symbol_short!("question"), &Vec::new(&env));
+        let open = client.create_post(&asker, &String::from_str(&env, "Cramps?"), &content, &symbol_short!("question"), &Vec::new(&env));
+        let story = client.create_post(&asker, &String::from_str(&env, "My story"), &content, &symbol_short!("story"), &Vec::new(&env));
+        for post_id in [&answered, &open, &story] {
+            client.moderate_post(&owner, post_id, &true, &String::from_str(&env, "ok"));
+        }
+
+        let answer = client.add_comment(&helper, &answered, &String::from_str(&env, "Often normal mid-cycle"), &None, &Vec::new(&env));
+        let res = client.try_mark_verified_answer(&helper, &answer);
+        assert_eq!(res, Err(Ok(CommunityError::NotExpert)));
+        client.mark_verified_answer(&gynaecologist, &answer);
+        client.mark_verified_answer(&midwife, &answer);
+        let res = client.try_mark_verified_answer(&midwife, &answer);
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        assert_eq!(client.get_comment(&answer).verified_by.len(), 2);
+        assert_eq!(client.get_post(&answered).verified_answers, 1);
+
+        let reply = client.add_comment(&helper, &story, &String::from_str(&env, "Thanks for sharing"), &None, &Vec::new(&env));
+        let res = client.try_mark_verified_answer(&gynaecologist, &reply);
+        assert_eq!(res, Err(Ok(CommunityError::NotAQuestion)));
+
+        let unanswered = client.list_unanswered_questions(&symbol_short!("question"), &0, &10).posts;
+        assert_eq!(unanswered.len(), 1);
+        assert_eq!(unanswered.get_unchecked(0).id, open);
+        client.archive_category(&owner, &symbol_short!("question"), &true);
+        assert_eq!(client.list_unanswered_questions(&symbol_short!("question"), &0, &10).posts.len(), 0);
+        client.archive_category(&owner, &symbol_short!("question"), &false);
+
+        client.revoke_expert_badge(&owner, &midwife);
+        assert_eq!(client.try_get_expert_badge(&midwife), Err(Ok(CommunityError::NotExpert)));
+    }
+
+    #[test]
+    fn test_comments_and_replies() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        client.initialize(&owner, &Address::generate(&env));
+        add_categories(&client, &owner);
+        let author = Address::generate(&env);
+        let commenter = Address::generate(&env);
+
+        let post_id = client.create_post(&author, &String::from_str(&env, "Cramps"), &String::from_str(&env, "Any tips?"), &symbol_short!("question"), &Vec::new(&env));
+        let text = String::from_str(&env, "Heat helps");
+
+        // Comments are only allowed once the post is approved
+        let res = client.try_add_comment(&commenter, &post_id, &text, &None, &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::PostNotApproved)));
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+
+        let comment_id = client.add_comment(&commenter, &post_id, &text, &None, &Vec::new(&env));
+        let reply_id = client.add_comment(&author, &post_id, &String::from_str(&env, "Thanks!"), &Some(comment_id.clone()), &Vec::new(&env));
+
+        let comment = client.get_comment(&comment_id);
+        assert_eq!(comment.parent_id, post_id);
+        assert_eq!(comment.replies.len(), 1);
+        assert_eq!(client.get_comment(&reply_id).parent_id, comment_id);
+        assert_eq!(client.get_post(&post_id).comment_count, 2);
+
+        let page = client.list_comments(&post_id, &1, &10);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page.get_unchecked(0).id, reply_id);
+    }
 
+    #[test]
+    fn test_edit_like_and_delete_comment() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        client.initialize(&owner, &Address::generate(&env));
+        add_categories(&client, &owner);
         let author = Address::generate(&env);
-        let title = String::from_str(&env, "Test Post");
-        let content = String::from_str(&env, "This is a test post.");
-        let category = symbol_short!("edu");
-        
-        let post_id = client.create_post(&author, &title, &content, &category);
-        
-        let post = client.get_post(&post_id);
-        assert_eq!(post.status, PostStatus::Pending);
-        
-        client.moderate_post(&owner, &post_id, &true);
-        
-        let post_after_mod = client.get_post(&post_id);
-        assert_eq!(post_after_mod.status, PostStatus::Approved);
-        
-        let reward = client.get_user_rewards(&author);
-        assert_eq!(reward, 10);
+        let commenter = Address::generate(&env);
+        let reader = Address::generate(&env);
+
+        let post_id = client.create_post(&author, &String::from_str(&env, "Sleep"), &String::from_str(&env, "Tracking sleep"), &symbol_short!("edu"), &Vec::new(&env));
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        let comment_id = client.add_comment(&commenter, &post_id, &String::from_str(&env, "Teh app"), &None, &Vec::new(&env));
+
+        let fixed = String::from_str(&env, "The app");
+        let res = client.try_edit_comment(&reader, &comment_id, &fixed);
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        client.edit_comment(&commenter, &comment_id, &fixed);
+        assert_eq!(client.get_comment(&comment_id).content, fixed);
+
+        client.like_comment(&reader, &comment_id);
+        let res = client.try_like_comment(&reader, &comment_id);
+        assert_eq!(res, Err(Ok(CommunityError::AlreadyLiked)));
+        assert_eq!(client.get_comment(&comment_id).likes, 1);
+
+        // Moderators can remove any comment; removal is soft
+        client.delete_comment(&owner, &comment_id, &String::from_str(&env, "spam"));
+        assert!(client.get_comment(&comment_id).is_removed);
+        assert_eq!(client.get_moderation_log(&comment_id).get_unchecked(0).reason, String::from_str(&env, "spam"));
+        let res = client.try_add_comment(&reader, &post_id, &fixed, &Some(comment_id.clone()), &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::CommentRemoved)));
+    }
+
+    #[test]
+    fn test_reports_hide_content_and_queue_by_count() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+        client.set_report_threshold(&owner, &2);
+        let author = Address::generate(&env);
+
+        let quiet = client.create_post(&author, &String::from_str(&env, "Iron"), &String::from_str(&env, "Supplements?"), &symbol_short!("edu"), &Vec::new(&env));
+        let loud = client.create_post(&author, &String::from_str(&env, "Buy now"), &String::from_str(&env, "Miracle pills"), &symbol_short!("edu"), &Vec::new(&env));
+        client.moderate_post(&owner, &quiet, &true, &String::from_str(&env, "ok"));
+        client.moderate_post(&owner, &loud, &true, &String::from_str(&env, "ok"));
+        let other = client.create_post(&author, &String::from_str(&env, "Zinc"), &String::from_str(&env, "Dosage?"), &symbol_short!("edu"), &Vec::new(&env));
+        client.moderate_post(&owner, &other, &true, &String::from_str(&env, "ok"));
+
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+        let res = client.try_report_post(&first, &quiet, &ReportReason::Other);
+        assert_eq!(res, Err(Ok(CommunityError::NotRegistered)));
+        registry.register_user(&first);
+        registry.register_user(&second);
+        client.report_post(&first, &quiet, &ReportReason::Other);
+        client.report_post(&first, &loud, &ReportReason::Spam);
+        let res = client.try_report_post(&first, &loud, &ReportReason::Spam);
+        assert_eq!(res, Err(Ok(CommunityError::AlreadyReported)));
+        assert!(!client.get_post(&loud).is_hidden);
+
+        // Reaching the threshold hides the post and moves it to the front of the queue
+        assert_eq!(client.report_post(&second, &loud, &ReportReason::Spam), 2);
+        assert!(client.get_post(&loud).is_hidden);
+        assert_eq!(client.list_approved_posts(&FeedSort::Newest, &0, &10).posts.len(), 2);
+        let res = client.try_vote(&first, &loud, &VoteType::Upvote);
+        assert_eq!(res, Err(Ok(CommunityError::PostNotApproved)));
+        let res = client.try_add_comment(&first, &loud, &String::from_str(&env, "Scam"), &None, &Vec::new(&env));
+        assert_eq!(res, Err(Ok(CommunityError::PostNotApproved)));
+
+        client.report_post(&second, &other, &ReportReason::Other);
+        let queue = client.list_moderation_queue(&0, &10);
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.get_unchecked(0).target_id, loud);
+        assert_eq!(queue.get_unchecked(0).report_count, 2);
+        assert_eq!(queue.get_unchecked(1).target_id, quiet);
+        assert_eq!(client.list_moderation_queue(&1, &1).get_unchecked(0).target_id, quiet);
+        assert_eq!(client.list_moderation_queue(&2, &10).get_unchecked(0).target_id, other);
+
+        // Handled content leaves its bucket and the last entry takes its place
+        client.dismiss_reports(&owner, &quiet, &String::from_str(&env, "ok"));
+        let queue = client.list_moderation_queue(&0, &10);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.get_unchecked(1).target_id, other);
+        client.remove_post(&owner, &loud, &String::from_str(&env, "ok"));
+        assert!(client.get_post(&loud).is_removed);
+        client.dismiss_reports(&owner, &other, &String::from_str(&env, "ok"));
+        assert_eq!(client.list_moderation_queue(&0, &10).len(), 0);
+        let res = client.try_dismiss_reports(&owner, &loud, &String::from_str(&env, "ok"));
+        assert_eq!(res, Err(Ok(CommunityError::ReportNotFound)));
+    }
+
+    #[test]
+    fn test_appeal_restores_removed_comment() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+        client.set_report_threshold(&owner, &1);
+        client.set_reward_rate(&owner, &RewardAction::CommentAdded, &3);
+        let author = Address::generate(&env);
+        let commenter = Address::generate(&env);
+        registry.register_user(&author);
+
+        let post_id = client.create_post(&author, &String::from_str(&env, "PCOS"), &String::from_str(&env, "Diagnosis story"), &symbol_short!("story"), &Vec::new(&env));
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        let comment_id = client.add_comment(&commenter, &post_id, &String::from_str(&env, "Same here"), &None, &Vec::new(&env));
+        assert_eq!(client.get_user_rewards(&commenter), 3);
+        let statement = String::from_str(&env, "This was not spam");
+
+        let res = client.try_appeal(&commenter, &comment_id, &ReportTarget::Comment, &statement);
+        assert_eq!(res, Err(Ok(CommunityError::NotRemoved)));
+
+        client.report_comment(&author, &comment_id, &ReportReason::Spam);
+        assert!(client.get_comment(&comment_id).is_hidden);
+        assert_eq!(client.list_comments(&post_id, &0, &10).len(), 0);
+        client.delete_comment(&owner, &comment_id, &String::from_str(&env, "spam"));
+        assert_eq!(client.list_moderation_queue(&0, &10).len(), 0);
+        assert_eq!(client.get_user_rewards(&commenter), 0);
+
+        let res = client.try_appeal(&author, &comment_id, &ReportTarget::Comment, &statement);
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+        client.appeal(&commenter, &comment_id, &ReportTarget::Comment, &statement);
+        let res = client.try_appeal(&commenter, &comment_id, &ReportTarget::Comment, &statement);
+        assert_eq!(res, Err(Ok(CommunityError::AlreadyAppealed)));
+        assert_eq!(client.list_appeals(&0, &10).len(), 1);
+
+        client.resolve_appeal(&owner, &comment_id, &true, &String::from_str(&env, "ok"));
+        assert!(!client.get_comment(&comment_id).is_removed);
+        assert_eq!(client.get_user_rewards(&commenter), 3);
+        assert_eq!(client.get_appeal(&comment_id).status, AppealStatus::Upheld);
+        assert_eq!(client.list_appeals(&0, &10).len(), 0);
+        let res = client.try_resolve_appeal(&owner, &comment_id, &false, &String::from_str(&env, "ok"));
+        assert_eq!(res, Err(Ok(CommunityError::AppealNotFound)));
+    }
+
+    fn commit_identity(env: &Env, author: &Address, salt: &BytesN<32>) -> BytesN<32> {
+        let mut bytes = author.clone().to_xdr(env);
+        bytes.extend_from_array(&salt.to_array());
+        env.crypto().sha256(&bytes)
+    }
+    
+    fn sign_message(env: &Env, key: &ed25519_dalek::SigningKey, message: Bytes) -> BytesN<64> {
+        use ed25519_dalek::Signer;
+        let message: std::vec::Vec<u8> = message.iter().collect();
+        BytesN::from_array(env, &key.sign(&message).to_bytes())
+    }
+    
+    fn post_anonymously(client: &CommunityContractClient, key: &ed25519_dalek::SigningKey, title: &str, content: &str, category: Symbol, commitment: &BytesN<32>, escrow: &Bytes) -> BytesN<32> {
+        let env = &client.env;
+        let author_key = BytesN::from_array(env, &key.verifying_key().to_bytes());
+        let title = String::from_str(env, title);
+        let content = String::from_str(env, content);
+        let message = client.anonymous_post_message(&title, &content, &category, &author_key, commitment, escrow);
+        client.create_anonymous_post(&title, &content, &category, &author_key, commitment, escrow, &sign_message(env, key, message))
+    }
+    
+    fn comment_anonymously(client: &CommunityContractClient, key: &ed25519_dalek::SigningKey, post_id: &BytesN<32>, content: &str, commitment: &BytesN<32>, escrow: &Bytes) -> BytesN<32> {
+        let env = &client.env;
+        let author_key = BytesN::from_array(env, &key.verifying_key().to_bytes());
+        let content = String::from_str(env, content);
+        let message = client.anonymous_comment_message(post_id, &content, &None, &author_key, commitment, escrow);
+        client.add_anonymous_comment(post_id, &content, &None, &author_key, commitment, escrow, &sign_message(env, key, message))
     }
     
+    fn sign_authorship(client: &CommunityContractClient, key: &ed25519_dalek::SigningKey, target_id: &BytesN<32>, action: Symbol, content: &String) -> BytesN<64> {
+        use ed25519_dalek::Signer;
+        let message: std::vec::Vec<u8> = client.authorship_message(target_id, &action, content).iter().collect();
+        BytesN::from_array(&client.env, &key.sign(&message).to_bytes())
+    }
+
     #[test]
-    fn test_voting() {
+    fn test_anonymous_post_and_comments() {
         let env = Env::default();
         env.mock_all_auths();
         let contract_id = env.register_contract(None, CommunityContract);
         let client = CommunityContractClient::new(&env, &contract_id);
 
         let owner = Address::generate(&env);
-        client.initialize(&owner, &Address::generate(&env));
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+
+        // Only the author key signs; the author's address never appears in the call
         let author = Address::generate(&env);
-        let voter = Address::generate(&env);
-        
-        let title = String::from_str(&env, "VotingPost");
-        let content = String::from_str(&env, "Vote here!");
-        let category = symbol_short!("poll");
-        
-        let post_id = client.create_post(&author, &title, &content, &category);
-        client.moderate_post(&owner, &post_id, &true); 
-        
-        client.vote(&voter, &post_id, &VoteType::Upvote);
-        let post_after_upvote = client.get_post(&post_id);
-        assert_eq!(post_after_upvote.upvotes, 1);
-        
-        client.vote(&voter, &post_id, &VoteType::Downvote);
-        let post_after_downvote = client.get_post(&post_id);
-        assert_eq!(post_after_downvote.upvotes, 0);
-        assert_eq!(post_after_downvote.downvotes, 1);
+        let salt = BytesN::from_array(&env, &[0xee; 32]);
+        let commitment = commit_identity(&env, &author, &salt);
+        let escrow = Bytes::from_array(&env, &[0xab; 48]);
+        let key = ed25519_dalek::SigningKey::from_bytes(&[21; 32]);
+        let post_id = post_anonymously(&client, &key, "Irregular cycles", "Is this normal?", symbol_short!("question"), &commitment, &escrow);
+        assert!(env.auths().is_empty());
 
-        client.vote(&voter, &post_id, &VoteType::Downvote);
-        let post_after_remove_vote = client.get_post(&post_id);
-        assert_eq!(post_after_remove_vote.downvotes, 0);
+        let post = client.get_post(&post_id);
+        assert!(post.anonymous);
+        assert_eq!(post.author, contract_id);
+        assert_eq!(client.get_identity_escrow(&post_id), escrow);
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        assert_eq!(client.get_user_rewards(&contract_id), 0);
+
+        // The author keeps their pseudonym in the thread by reusing the key
+        let comment_id = comment_anonymously(&client, &key, &post_id, "Update: saw a doctor", &commitment, &escrow);
+        assert!(env.auths().is_empty());
+        let comment = client.get_comment(&comment_id);
+        assert_eq!(comment.pseudonym, post.pseudonym);
+
+        let edited = String::from_str(&env, "Update: saw a clinician");
+        let sig = sign_authorship(&client, &key, &comment_id, symbol_short!("edit"), &edited);
+        client.edit_anonymous_comment(&comment_id, &edited, &sig);
+        assert_eq!(client.get_comment(&comment_id).content, edited);
+
+        let body = String::from_str(&env, "Is this normal at 16?");
+        let sig = sign_authorship(&client, &key, &post_id, symbol_short!("edit"), &body);
+        client.edit_anonymous_post(&post_id, &body, &sig);
+        assert_eq!(client.get_post(&post_id).content, body);
+        assert_eq!(client.get_post_history(&post_id).len(), 1);
+
+        let sig = sign_authorship(&client, &key, &post_id, symbol_short!("delete"), &String::from_str(&env, ""));
+        client.delete_anonymous_post(&post_id, &sig);
+        assert!(client.get_post(&post_id).is_removed);
+    }
+
+    #[test]
+    fn test_deleting_reported_anonymous_comment_clears_queue() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+        client.set_report_threshold(&owner, &1);
+        let author = Address::generate(&env);
+        let reporter = Address::generate(&env);
+        registry.register_user(&reporter);
+
+        let post_id = client.create_post(&author, &String::from_str(&env, "Cramps"), &String::from_str(&env, "Heat packs?"), &symbol_short!("edu"), &Vec::new(&env));
+        client.moderate_post(&owner, &post_id, &true, &String::from_str(&env, "ok"));
+        let key = ed25519_dalek::SigningKey::from_bytes(&[23; 32]);
+        let commitment = BytesN::from_array(&env, &[0xcc; 32]);
+        let comment_id = comment_anonymously(&client, &key, &post_id, "Try this link", &commitment, &Bytes::new(&env));
+
+        client.report_comment(&reporter, &comment_id, &ReportReason::Spam);
+        assert_eq!(client.list_moderation_queue(&0, &10).len(), 1);
+
+        let sig = sign_authorship(&client, &key, &comment_id, symbol_short!("delete"), &String::from_str(&env, ""));
+        client.delete_anonymous_comment(&comment_id, &sig);
+        assert!(client.get_comment(&comment_id).is_removed);
+        assert_eq!(client.list_moderation_queue(&0, &10).len(), 0);
+        let res = client.try_dismiss_reports(&owner, &comment_id, &String::from_str(&env, "ok"));
+        assert_eq!(res, Err(Ok(CommunityError::ReportNotFound)));
+    }
+
+    #[test]
+    fn test_reveal_anonymous_author_is_owner_only() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CommunityContract);
+        let client = CommunityContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let registry = setup_registry(&env);
+        client.initialize(&owner, &registry.address);
+        add_categories(&client, &owner);
+        let moderator = Address::generate(&env);
+        client.add_moderator(&owner, &moderator, &ModeratorRole::Global, &Vec::new(&env));
+
+        let author = Address::generate(&env);
+        let salt = BytesN::from_array(&env, &[0xee; 32]);
+        let commitment = commit_identity(&env, &author, &salt);
+        let key = ed25519_dalek::SigningKey::from_bytes(&[22; 32]);
+        let post_id = post_anonymously(&client, &key, "Title", "Body", symbol_short!("support"), &commitment, &Bytes::from_array(&env, &[0xab; 48]));
+
+        let res = client.try_reveal_anonymous_author(&moderator, &post_id, &symbol_short!("harm"), &author, &salt);
+        assert_eq!(res, Err(Ok(CommunityError::Unauthorized)));
+
+        // The owner cannot pin the post on someone else or guess the salt
+        let res = client.try_reveal_anonymous_author(&owner, &post_id, &symbol_short!("harm"), &moderator, &salt);
+        assert_eq!(res, Err(Ok(CommunityError::IdentityMismatch)));
+        let res = client.try_reveal_anonymous_author(&owner, &post_id, &symbol_short!("harm"), &author, &BytesN::from_array(&env, &[0; 32]));
+        assert_eq!(res, Err(Ok(CommunityError::IdentityMismatch)));
+        client.reveal_anonymous_author(&owner, &post_id, &symbol_short!("harm"), &author, &salt);
+
+        let public_post = client.create_post(&moderator, &String::from_str(&env, "T"), &String::from_str(&env, "B"), &symbol_short!("edu"), &Vec::new(&env));
+        let res = client.try_reveal_anonymous_author(&owner, &public_post, &symbol_short!("harm"), &author, &salt);
+        assert_eq!(res, Err(Ok(CommunityError::NotAnonymous)));
     }
-} 
\ No newline at end of file
+}
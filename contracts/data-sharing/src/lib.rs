@@ -1,76 +1,133 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Map, String, Symbol, Vec, BytesN};
+use soroban_sdk::{contract, contractclient, contracterror, contractimpl, contracttype, symbol_short, Address, BytesN, Env, Vec};
+use soroban_sdk::xdr::ToXdr;
 
+#[contracterror]
+#[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum DataSharingError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    Unauthorized = 3,
+    ShareNotFound = 4,
+    ShareInactive = 5,
+    ShareNotYetActive = 6,
+    ShareExpired = 7,
+    InvalidTimeBounds = 8,
+    NoDataIds = 9,
+    DataContractNotSet = 10,
+    DataNotOwned = 11,
+}
+
+// Access log entries stored per page
+pub const ACCESS_LOG_PAGE_SIZE: u32 = 50;
+// Maximum number of access log entries returned by one call
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+// Storage keys
 #[derive(Clone)]
 #[contracttype]
+pub enum DataKey {
+    Admin,                  // Address allowed to audit every share
+    ShareNonce,             // Counter mixed into share IDs so they never collide
+    Share(BytesN<32>),      // Share ID -> ShareAccess (persistent)
+    Outgoing(Address),      // Owner -> Vec<BytesN<32>> of share IDs (persistent)
+    Incoming(Address),      // Recipient -> Vec<BytesN<32>> of share IDs (persistent)
+    AccessLogCount(BytesN<32>), // Share ID -> number of access log entries (persistent)
+    AccessLogPage(BytesN<32>, u32), // (Share ID, page number) -> Vec<AccessLog> of up to ACCESS_LOG_PAGE_SIZE (persistent)
+    DataContract,           // Data contract holding the shared records
+}
+
+// Interface of the data contract used to verify record ownership
+#[contractclient(name = "DataClient")]
+pub trait DataInterface {
+    fn has_data(env: Env, user: Address, data_id: BytesN<32>) -> bool;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
 pub enum AccessLevel {
     ReadOnly,
     ReadWrite,
     FullAccess,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 #[contracttype]
 pub struct DataShareConfig {
-    owner: Address,
-    recipient: Address,
-    data_ids: Vec<BytesN<32>>, // Using hash identifiers for data
-    start_time: u64,
-    end_time: u64,
-    access_level: AccessLevel,
+    pub owner: Address,
+    pub recipient: Address,
+    pub data_ids: Vec<BytesN<32>>, // Using hash identifiers for data
+    pub start_time: u64,
+    pub end_time: u64,
+    pub access_level: AccessLevel,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 #[contracttype]
 pub struct ShareAccess {
-    config: DataShareConfig,
-    share_id: BytesN<32>,
-    is_active: bool,
+    pub config: DataShareConfig,
+    pub share_id: BytesN<32>,
+    pub is_active: bool,
 }
 
 #[derive(Clone)]
 #[contracttype]
 pub struct AccessRequest {
-    share_id: BytesN<32>,
-    requester: Address,
-    timestamp: u64,
+    pub share_id: BytesN<32>,
+    pub requester: Address,
+    pub timestamp: u64,
 }
 
-#[derive(Clone)]
+// A successful read through a share. Denied requests revert or return before
+// anything is written, so failed attempts never appear in the log.
+#[derive(Clone, Debug, PartialEq)]
 #[contracttype]
 pub struct AccessLog {
-    share_id: BytesN<32>,
-    requester: Address,
-    timestamp: u64,
-    data_ids: Vec<BytesN<32>>,
-    success: bool,
+    pub share_id: BytesN<32>,
+    pub requester: Address,
+    pub timestamp: u64,
+    pub data_ids: Vec<BytesN<32>>,
 }
 
 #[contract]
-pub struct DataSharingContract {
-    admin: Address,
-    shares: Map<BytesN<32>, ShareAccess>, // share_id -> ShareAccess
-    user_shares_outgoing: Map<Address, Vec<BytesN<32>>>, // owner -> list of share_ids
-    user_shares_incoming: Map<Address, Vec<BytesN<32>>>, // recipient -> list of share_ids
-    access_logs: Vec<AccessLog>,
-}
+pub struct DataSharingContract;
 
 #[contractimpl]
 impl DataSharingContract {
     // Initialize the contract
-    pub fn initialize(env: Env, admin: Address) -> Self {
-        admin.require_auth();
-        
-        Self {
-            admin,
-            shares: Map::new(&env),
-            user_shares_outgoing: Map::new(&env),
-            user_shares_incoming: Map::new(&env),
-            access_logs: Vec::new(&env),
+    pub fn initialize(env: Env, admin: Address) -> Result<(), DataSharingError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(DataSharingError::AlreadyInitialized);
         }
+
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Admin, &admin);
+
+        env.events().publish(
+            (symbol_short!("init"), symbol_short!("share")),
+            admin
+        );
+
+        Ok(())
+    }
+
+    // Set the data contract whose records can be shared (admin only)
+    pub fn set_data_contract(env: Env, data_contract: Address) -> Result<(), DataSharingError> {
+        let admin = Self::get_admin_internal(&env)?;
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::DataContract, &data_contract);
+
+        env.events().publish(
+            (symbol_short!("set_data"), symbol_short!("contract")),
+            data_contract
+        );
+
+        Ok(())
     }
-    
-    // Create a time-bound share with multi-signature requirement
+
+    // Create a time-bound share of the owner's data with a recipient. Every data ID must
+    // exist under the owner in the data contract.
     pub fn create_time_bound_share(
         env: Env,
         owner: Address,
@@ -79,348 +136,431 @@ impl DataSharingContract {
         start_time: u64,
         end_time: u64,
         access_level: AccessLevel,
-    ) -> BytesN<32> {
+    ) -> Result<BytesN<32>, DataSharingError> {
         owner.require_auth();
-        
+
         // Validate time bounds
-        let current_time = env.ledger().timestamp();
-        if start_time < current_time {
-            panic!("Start time must be in the future");
+        if start_time < env.ledger().timestamp() || end_time <= start_time {
+            return Err(DataSharingError::InvalidTimeBounds);
         }
-        
-        if end_time <= start_time {
-            panic!("End time must be after start time");
+
+        if data_ids.is_empty() {
+            return Err(DataSharingError::NoDataIds);
         }
-        
-        // Create the data share configuration
-        let config = DataShareConfig {
-            owner: owner.clone(),
-            recipient: recipient.clone(),
-            data_ids,
-            start_time,
-            end_time,
-            access_level,
-        };
-        
-        // Create a unique share ID (hash of config data + timestamp)
-        let share_id_preimage = env.crypto().sha256(
-            &String::from_str(&env, &format!("{:?}_{:?}_{}", owner, recipient, env.ledger().timestamp()))
-        );
-        
-        // Create the share access object
+
+        let data_contract = env.storage().instance().get::<DataKey, Address>(&DataKey::DataContract)
+            .ok_or(DataSharingError::DataContractNotSet)?;
+        let data_client = DataClient::new(&env, &data_contract);
+        for data_id in data_ids.iter() {
+            if !data_client.has_data(&owner, &data_id) {
+                return Err(DataSharingError::DataNotOwned);
+            }
+        }
+
+        // Create a unique share ID from the parties, the time and a counter
+        let nonce = env.storage().instance().get::<DataKey, u64>(&DataKey::ShareNonce).unwrap_or(0);
+        env.storage().instance().set(&DataKey::ShareNonce, &(nonce + 1));
+        let share_id = env.crypto().sha256(&(owner.clone(), recipient.clone(), env.ledger().timestamp(), nonce).to_xdr(&env));
+
         let share_access = ShareAccess {
-            config: config.clone(),
-            share_id: share_id_preimage.clone(),
+            config: DataShareConfig {
+                owner: owner.clone(),
+                recipient: recipient.clone(),
+                data_ids,
+                start_time,
+                end_time,
+                access_level,
+            },
+            share_id: share_id.clone(),
             is_active: true,
         };
-        
-        let contract = Self::load_contract(&env);
-        let mut contract = contract;
-        
-        // Store the share
-        contract.shares.set(share_id_preimage.clone(), share_access);
-        
+        env.storage().persistent().set(&DataKey::Share(share_id.clone()), &share_access);
+
         // Update owner's outgoing shares
-        let mut owner_shares = contract.user_shares_outgoing.get(owner.clone()).unwrap_or(Vec::new(&env));
-        owner_shares.push_back(share_id_preimage.clone());
-        contract.user_shares_outgoing.set(owner, owner_shares);
-        
+        let mut owner_shares = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&DataKey::Outgoing(owner.clone())).unwrap_or(Vec::new(&env));
+        owner_shares.push_back(share_id.clone());
+        env.storage().persistent().set(&DataKey::Outgoing(owner.clone()), &owner_shares);
+
         // Update recipient's incoming shares
-        let mut recipient_shares = contract.user_shares_incoming.get(recipient.clone()).unwrap_or(Vec::new(&env));
-        recipient_shares.push_back(share_id_preimage.clone());
-        contract.user_shares_incoming.set(recipient, recipient_shares);
-        
-        // In production, this would also create a real Stellar multi-signature 
-        // transaction with time bounds that authorizes the data access
-        Self::save_contract(&env, &contract);
-        
-        share_id_preimage
+        let mut recipient_shares = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&DataKey::Incoming(recipient.clone())).unwrap_or(Vec::new(&env));
+        recipient_shares.push_back(share_id.clone());
+        env.storage().persistent().set(&DataKey::Incoming(recipient.clone()), &recipient_shares);
+
+        env.events().publish(
+            (symbol_short!("share"), symbol_short!("create")),
+            (share_id.clone(), owner, recipient)
+        );
+
+        Ok(share_id)
     }
-    
+
     // Access shared data (requires authentication from recipient)
     pub fn access_shared_data(
         env: Env,
         share_id: BytesN<32>,
         requester: Address,
-    ) -> Result<Vec<BytesN<32>>, String> {
+    ) -> Result<Vec<BytesN<32>>, DataSharingError> {
         requester.require_auth();
-        
-        let contract = Self::load_contract(&env);
-        
-        // Get the share
-        let share = contract.shares.get(share_id.clone())
-            .ok_or(String::from_str(&env, "Share not found"))?;
-        
-        // Verify the share is active
-        if !share.is_active {
-            return Err(String::from_str(&env, "Share is no longer active"));
-        }
-        
-        // Verify the requester is the intended recipient
-        if share.config.recipient != requester {
-            return Err(String::from_str(&env, "Unauthorized requester"));
-        }
-        
-        // Verify time bounds
-        let current_time = env.ledger().timestamp();
-        
-        if current_time < share.config.start_time {
-            return Err(String::from_str(&env, "Share is not yet active"));
-        }
-        
-        if current_time > share.config.end_time {
-            // Deactivate expired shares
-            let mut updated_share = share.clone();
-            updated_share.is_active = false;
-            
-            let mut contract = contract;
-            contract.shares.set(share_id.clone(), updated_share);
-            Self::save_contract(&env, &contract);
-            
-            return Err(String::from_str(&env, "Share has expired"));
-        }
-        
-        // Log the access
-        let log = AccessLog {
-            share_id: share_id.clone(),
-            requester: requester.clone(),
-            timestamp: current_time,
-            data_ids: share.config.data_ids.clone(),
-            success: true,
-        };
-        
-        let mut contract = contract;
-        contract.access_logs.push_back(log);
-        Self::save_contract(&env, &contract);
-        
+
+        let share = Self::get_usable_share(&env, &share_id, &requester)?;
+        Self::log_access(&env, share_id, requester, share.config.data_ids.clone());
+
         // Return the data IDs that can be accessed
-        // In a real implementation, this would also verify the multi-signature transaction
         Ok(share.config.data_ids)
     }
-    
+
+    // Whether the requester may read one shared record right now; called by the data
+    // contract from get_shared_data. Allowed reads are logged against the share.
+    pub fn use_share(
+        env: Env,
+        share_id: BytesN<32>,
+        requester: Address,
+        data_owner: Address,
+        data_id: BytesN<32>,
+    ) -> bool {
+        requester.require_auth();
+
+        let share = match Self::get_usable_share(&env, &share_id, &requester) {
+            Ok(share) => share,
+            Err(_) => return false,
+        };
+        if share.config.owner != data_owner || !share.config.data_ids.contains(&data_id) {
+            return false;
+        }
+
+        Self::log_access(&env, share_id, requester, Vec::from_array(&env, [data_id]));
+        true
+    }
+
     // Revoke a share (only owner can revoke)
     pub fn revoke_share(
         env: Env,
         owner: Address,
         share_id: BytesN<32>,
-    ) -> Result<(), String> {
+    ) -> Result<(), DataSharingError> {
         owner.require_auth();
-        
-        let contract = Self::load_contract(&env);
-        
-        // Get the share
-        let share = contract.shares.get(share_id.clone())
-            .ok_or(String::from_str(&env, "Share not found"))?;
-        
+
+        let mut share = Self::get_share(env.clone(), share_id.clone())?;
+
         // Verify the caller is the owner
         if share.config.owner != owner {
-            return Err(String::from_str(&env, "Only the owner can revoke shares"));
+            return Err(DataSharingError::Unauthorized);
         }
-        
+
+        if !share.is_active {
+            return Err(DataSharingError::ShareInactive);
+        }
+
         // Deactivate the share
-        let mut updated_share = share;
-        updated_share.is_active = false;
-        
-        let mut contract = contract;
-        contract.shares.set(share_id, updated_share);
-        Self::save_contract(&env, &contract);
-        
-        // In production, this would also cancel the multi-signature transaction
-        
+        share.is_active = false;
+        env.storage().persistent().set(&DataKey::Share(share_id.clone()), &share);
+
+        env.events().publish(
+            (symbol_short!("share"), symbol_short!("revoke")),
+            share_id
+        );
+
         Ok(())
     }
-    
+
     // Get shares created by a user
     pub fn get_outgoing_shares(
         env: Env,
         owner: Address,
     ) -> Vec<ShareAccess> {
-        let contract = Self::load_contract(&env);
+        let share_ids = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&DataKey::Outgoing(owner)).unwrap_or(Vec::new(&env));
         let mut result = Vec::new(&env);
-        
-        if let Some(share_ids) = contract.user_shares_outgoing.get(owner) {
-            for share_id in share_ids.iter() {
-                if let Some(share) = contract.shares.get(share_id) {
-                    result.push_back(share);
-                }
+
+        for share_id in share_ids.iter() {
+            if let Some(share) = env.storage().persistent().get::<DataKey, ShareAccess>(&DataKey::Share(share_id)) {
+                result.push_back(share);
             }
         }
-        
+
         result
     }
-    
+
     // Get shares available to a user
     pub fn get_incoming_shares(
         env: Env,
         recipient: Address,
     ) -> Vec<ShareAccess> {
-        let contract = Self::load_contract(&env);
+        let share_ids = env.storage().persistent().get::<DataKey, Vec<BytesN<32>>>(&DataKey::Incoming(recipient)).unwrap_or(Vec::new(&env));
         let mut result = Vec::new(&env);
-        
-        if let Some(share_ids) = contract.user_shares_incoming.get(recipient) {
-            for share_id in share_ids.iter() {
-                if let Some(share) = contract.shares.get(share_id) {
-                    // Only return active shares
-                    if share.is_active {
-                        result.push_back(share);
-                    }
+
+        for share_id in share_ids.iter() {
+            if let Some(share) = env.storage().persistent().get::<DataKey, ShareAccess>(&DataKey::Share(share_id)) {
+                // Only return active shares
+                if share.is_active {
+                    result.push_back(share);
                 }
             }
         }
-        
+
         result
     }
-    
+
     // Get share details
     pub fn get_share(
         env: Env,
         share_id: BytesN<32>,
-    ) -> Option<ShareAccess> {
-        let contract = Self::load_contract(&env);
-        contract.shares.get(share_id)
+    ) -> Result<ShareAccess, DataSharingError> {
+        env.storage().persistent().get::<DataKey, ShareAccess>(&DataKey::Share(share_id)).ok_or(DataSharingError::ShareNotFound)
     }
-    
-    // Get access logs for a share (admin or owner can view)
+
+    // Get a share's access log, oldest first, starting at index `cursor` (admin or owner can view)
     pub fn get_access_logs(
         env: Env,
         caller: Address,
         share_id: BytesN<32>,
-    ) -> Result<Vec<AccessLog>, String> {
+        cursor: u32,
+        limit: u32,
+    ) -> Result<Vec<AccessLog>, DataSharingError> {
         caller.require_auth();
-        
-        let contract = Self::load_contract(&env);
-        
-        // Get the share
-        let share = contract.shares.get(share_id.clone())
-            .ok_or(String::from_str(&env, "Share not found"))?;
-        
+
+        let admin = Self::get_admin_internal(&env)?;
+        let share = Self::get_share(env.clone(), share_id.clone())?;
+
         // Verify caller is admin or owner
-        if caller != contract.admin && caller != share.config.owner {
-            return Err(String::from_str(&env, "Unauthorized"));
+        if caller != admin && caller != share.config.owner {
+            return Err(DataSharingError::Unauthorized);
         }
-        
-        // Filter logs for this share
-        let mut result = Vec::new(&env);
-        
-        for log in contract.access_logs.iter() {
-            if log.share_id == share_id {
-                result.push_back(log);
+
+        let count = env.storage().persistent().get::<DataKey, u32>(&DataKey::AccessLogCount(share_id.clone())).unwrap_or(0);
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+
+        let mut logs = Vec::new(&env);
+        let mut i = cursor;
+        while i < end {
+            let page_number = i / ACCESS_LOG_PAGE_SIZE;
+            let page_end = ((page_number + 1) * ACCESS_LOG_PAGE_SIZE).min(end);
+            let page = env.storage().persistent().get::<DataKey, Vec<AccessLog>>(&DataKey::AccessLogPage(share_id.clone(), page_number)).unwrap_or(Vec::new(&env));
+            for j in i..page_end {
+                if let Some(log) = page.get(j % ACCESS_LOG_PAGE_SIZE) {
+                    logs.push_back(log);
+                }
             }
+            i = page_end;
         }
-        
-        Ok(result)
+        Ok(logs)
     }
-    
-    // Helper function to load contract state
-    fn load_contract(env: &Env) -> DataSharingContract {
-        // In a full implementation, we'd load from storage
-        // For prototype, return empty contract
-        DataSharingContract {
-            admin: Address::from_string(env, "GBUKOFF6FX6767LKKOD3P7KAS43I3Z7CNUBPCH33YZKPPR53ZDHAHCER"),
-            shares: Map::new(env),
-            user_shares_outgoing: Map::new(env),
-            user_shares_incoming: Map::new(env),
-            access_logs: Vec::new(env),
+
+    // Load a share the requester may use right now
+    fn get_usable_share(env: &Env, share_id: &BytesN<32>, requester: &Address) -> Result<ShareAccess, DataSharingError> {
+        let share = Self::get_share(env.clone(), share_id.clone())?;
+
+        // Verify the share is active
+        if !share.is_active {
+            return Err(DataSharingError::ShareInactive);
+        }
+
+        // Verify the requester is the intended recipient
+        if share.config.recipient != *requester {
+            return Err(DataSharingError::Unauthorized);
+        }
+
+        // Verify time bounds
+        let current_time = env.ledger().timestamp();
+        if current_time < share.config.start_time {
+            return Err(DataSharingError::ShareNotYetActive);
         }
+        if current_time > share.config.end_time {
+            return Err(DataSharingError::ShareExpired);
+        }
+
+        Ok(share)
     }
-    
-    // Helper function to save contract state
-    fn save_contract(env: &Env, contract: &DataSharingContract) {
-        // In a full implementation, we'd save to storage
+
+    // Append to a share's paged access log. Failed attempts revert with their error, so only
+    // successes are stored.
+    fn log_access(env: &Env, share_id: BytesN<32>, requester: Address, data_ids: Vec<BytesN<32>>) {
+        let log = AccessLog {
+            share_id: share_id.clone(),
+            requester: requester.clone(),
+            timestamp: env.ledger().timestamp(),
+            data_ids,
+        };
+
+        let count = env.storage().persistent().get::<DataKey, u32>(&DataKey::AccessLogCount(share_id.clone())).unwrap_or(0);
+        let page_key = DataKey::AccessLogPage(share_id.clone(), count / ACCESS_LOG_PAGE_SIZE);
+        let mut page = env.storage().persistent().get::<DataKey, Vec<AccessLog>>(&page_key).unwrap_or(Vec::new(env));
+        page.push_back(log);
+        env.storage().persistent().set(&page_key, &page);
+        env.storage().persistent().set(&DataKey::AccessLogCount(share_id.clone()), &(count + 1));
+
+        env.events().publish(
+            (symbol_short!("share"), symbol_short!("access")),
+            (share_id, requester)
+        );
+    }
+
+    // Get the contract admin
+    fn get_admin_internal(env: &Env) -> Result<Address, DataSharingError> {
+        match env.storage().instance().get::<DataKey, Address>(&DataKey::Admin) {
+            Some(admin) => Ok(admin),
+            None => Err(DataSharingError::NotInitialized),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Env, BytesN};
-    
+    use soroban_sdk::{testutils::{Address as _, Ledger}, Bytes, Env, BytesN};
+    use cyclebuddy_data_contract::{DataContract, DataContractClient, DataError};
+
+    // Register and wire up a data contract, storing `count` records for `owner`
+    fn setup_data(env: &Env, client: &DataSharingContractClient, sharing_id: &Address, owner: &Address, count: u32) -> (DataContractClient<'static>, Vec<BytesN<32>>) {
+        let data_id = env.register_contract(None, DataContract);
+        let data_client = DataContractClient::new(env, &data_id);
+        data_client.initialize(&Address::generate(env), &Address::generate(env));
+        data_client.set_sharing_contract(sharing_id);
+        client.set_data_contract(&data_id);
+
+        let mut data_ids = Vec::new(env);
+        for i in 0..count {
+            let content = Bytes::from_array(env, &[i as u8; 32]);
+            data_ids.push_back(data_client.store_data(owner, &content, &Bytes::new(env), &0));
+        }
+        (data_client, data_ids)
+    }
+
     #[test]
     fn test_data_sharing_flow() {
         let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DataSharingContract);
+        let client = DataSharingContractClient::new(&env, &contract_id);
+
         let admin = Address::generate(&env);
         let owner = Address::generate(&env);
         let recipient = Address::generate(&env);
-        
+        let stranger = Address::generate(&env);
+
         // Initialize contract
-        let contract = DataSharingContract::initialize(env.clone(), admin);
-        
-        // Create some data IDs
-        let data_id1 = BytesN::from_array(&env, &[1u8; 32]);
-        let data_id2 = BytesN::from_array(&env, &[2u8; 32]);
-        
-        let mut data_ids = Vec::new(&env);
-        data_ids.push_back(data_id1);
-        data_ids.push_back(data_id2);
-        
+        client.initialize(&admin);
+        assert_eq!(client.try_initialize(&admin), Err(Ok(DataSharingError::AlreadyInitialized)));
+
+        // Store some records in the data contract
+        let unshared = Vec::from_array(&env, [BytesN::from_array(&env, &[1u8; 32])]);
+        let res = client.try_create_time_bound_share(&owner, &recipient, &unshared, &10, &100, &AccessLevel::ReadOnly);
+        assert_eq!(res, Err(Ok(DataSharingError::DataContractNotSet)));
+        let (_, data_ids) = setup_data(&env, &client, &contract_id, &owner, 2);
+
+        // Shares can only cover records the owner actually holds
+        let res = client.try_create_time_bound_share(&owner, &recipient, &unshared, &10, &100, &AccessLevel::ReadOnly);
+        assert_eq!(res, Err(Ok(DataSharingError::DataNotOwned)));
+        let res = client.try_create_time_bound_share(&stranger, &recipient, &data_ids, &10, &100, &AccessLevel::ReadOnly);
+        assert_eq!(res, Err(Ok(DataSharingError::DataNotOwned)));
+
         // Set up time bounds (current time + 10, current time + 100)
         let current_time = env.ledger().timestamp();
         let start_time = current_time + 10;
         let end_time = current_time + 100;
-        
-        // Create a share
-        let share_id = DataSharingContract::create_time_bound_share(
-            env.clone(),
-            owner.clone(),
-            recipient.clone(),
-            data_ids,
-            start_time,
-            end_time,
-            AccessLevel::ReadOnly,
-        );
-        
-        // Check owner's outgoing shares
-        let owner_shares = DataSharingContract::get_outgoing_shares(env.clone(), owner.clone());
-        assert_eq!(owner_shares.len(), 1);
-        
-        // Check recipient's incoming shares
-        let recipient_shares = DataSharingContract::get_incoming_shares(env.clone(), recipient.clone());
-        assert_eq!(recipient_shares.len(), 1);
-        
+
+        let res = client.try_create_time_bound_share(&owner, &recipient, &data_ids, &end_time, &start_time, &AccessLevel::ReadOnly);
+        assert_eq!(res, Err(Ok(DataSharingError::InvalidTimeBounds)));
+        let res = client.try_create_time_bound_share(&owner, &recipient, &Vec::new(&env), &start_time, &end_time, &AccessLevel::ReadOnly);
+        assert_eq!(res, Err(Ok(DataSharingError::NoDataIds)));
+
+        // Create two shares in the same ledger; their IDs must differ
+        let share_id = client.create_time_bound_share(&owner, &recipient, &data_ids, &start_time, &end_time, &AccessLevel::ReadOnly);
+        let expiring_id = client.create_time_bound_share(&owner, &recipient, &data_ids, &start_time, &(start_time + 1), &AccessLevel::ReadOnly);
+        assert_ne!(share_id, expiring_id);
+
+        // Check owner's outgoing and recipient's incoming shares
+        assert_eq!(client.get_outgoing_shares(&owner).len(), 2);
+        assert_eq!(client.get_incoming_shares(&recipient).len(), 2);
+
         // Try to access before start time (should fail)
-        let result = DataSharingContract::access_shared_data(
-            env.clone(),
-            share_id.clone(),
-            recipient.clone(),
-        );
-        assert!(result.is_err());
-        
+        let res = client.try_access_shared_data(&share_id, &recipient);
+        assert_eq!(res, Err(Ok(DataSharingError::ShareNotYetActive)));
+
         // Advance time to after start_time
-        env.ledger().set(soroban_sdk::ledger::LedgerInfo {
-            timestamp: start_time + 5,
-            protocol_version: 20,
-            sequence_number: 100,
-            network_id: BytesN::from_array(&env, &[0; 32]),
-            base_reserve: 10,
-            min_persistent_entry_ttl: 100,
-            min_temp_entry_ttl: 100,
-            max_persistent_entry_ttl: 200,
-            max_temp_entry_ttl: 200,
-            max_persistent_entry_size: 100,
-            max_temp_entry_size: 100,
-        });
-        
-        // Now access should work
-        let result = DataSharingContract::access_shared_data(
-            env.clone(),
-            share_id.clone(),
-            recipient.clone(),
-        );
-        assert!(result.is_ok());
-        
-        // Check access logs
-        let logs = DataSharingContract::get_access_logs(env.clone(), owner.clone(), share_id.clone()).unwrap();
+        env.ledger().with_mut(|li| li.timestamp = start_time + 5);
+
+        // Now access should work, but only for the recipient
+        assert_eq!(client.access_shared_data(&share_id, &recipient), data_ids);
+        let res = client.try_access_shared_data(&share_id, &stranger);
+        assert_eq!(res, Err(Ok(DataSharingError::Unauthorized)));
+        let res = client.try_access_shared_data(&expiring_id, &recipient);
+        assert_eq!(res, Err(Ok(DataSharingError::ShareExpired)));
+
+        // Logs are kept per share and visible to the owner and admin only
+        let logs = client.get_access_logs(&owner, &share_id, &0, &10);
         assert_eq!(logs.len(), 1);
-        
+        assert_eq!(logs.get_unchecked(0).requester, recipient);
+        assert_eq!(client.get_access_logs(&admin, &share_id, &0, &10).len(), 1);
+        assert_eq!(client.get_access_logs(&owner, &expiring_id, &0, &10).len(), 0);
+        let res = client.try_get_access_logs(&stranger, &share_id, &0, &10);
+        assert_eq!(res, Err(Ok(DataSharingError::Unauthorized)));
+
+        // Later reads append to the log, which is read page by page
+        client.access_shared_data(&share_id, &recipient);
+        assert_eq!(client.get_access_logs(&owner, &share_id, &0, &10).len(), 2);
+        assert_eq!(client.get_access_logs(&owner, &share_id, &1, &10).len(), 1);
+        assert_eq!(client.get_access_logs(&owner, &share_id, &0, &1).len(), 1);
+
         // Revoke the share
-        DataSharingContract::revoke_share(env.clone(), owner, share_id.clone()).unwrap();
-        
+        let res = client.try_revoke_share(&recipient, &share_id);
+        assert_eq!(res, Err(Ok(DataSharingError::Unauthorized)));
+        client.revoke_share(&owner, &share_id);
+        assert!(!client.get_share(&share_id).is_active);
+        assert_eq!(client.get_incoming_shares(&recipient).len(), 1);
+
         // Try to access after revocation (should fail)
-        let result = DataSharingContract::access_shared_data(
-            env.clone(),
-            share_id,
-            recipient,
-        );
-        assert!(result.is_err());
+        let res = client.try_access_shared_data(&share_id, &recipient);
+        assert_eq!(res, Err(Ok(DataSharingError::ShareInactive)));
+        let res = client.try_get_share(&BytesN::from_array(&env, &[9u8; 32]));
+        assert_eq!(res, Err(Ok(DataSharingError::ShareNotFound)));
+    }
+
+    #[test]
+    fn test_share_gates_data_reads() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DataSharingContract);
+        let client = DataSharingContractClient::new(&env, &contract_id);
+
+        let owner = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let stranger = Address::generate(&env);
+        client.initialize(&Address::generate(&env));
+
+        let (data_client, data_ids) = setup_data(&env, &client, &contract_id, &owner, 2);
+        let shared_id = data_ids.get_unchecked(0);
+        let private_id = data_ids.get_unchecked(1);
+
+        let share_id = client.create_time_bound_share(&owner, &recipient, &Vec::from_array(&env, [shared_id.clone()]), &10, &100, &AccessLevel::ReadOnly);
+
+        // Without a permission or share the read is refused
+        let res = data_client.try_get_data(&owner, &recipient, &shared_id);
+        assert_eq!(res, Err(Ok(DataError::UserNotAuthorized)));
+
+        // The share only opens its window, its recipient and its records
+        let res = data_client.try_get_shared_data(&owner, &recipient, &shared_id, &share_id);
+        assert_eq!(res, Err(Ok(DataError::UserNotAuthorized)));
+        env.ledger().with_mut(|li| li.timestamp = 50);
+        let data = data_client.get_shared_data(&owner, &recipient, &shared_id, &share_id);
+        assert_eq!(data.encrypted_content, Bytes::from_array(&env, &[0u8; 32]));
+        let res = data_client.try_get_shared_data(&owner, &recipient, &private_id, &share_id);
+        assert_eq!(res, Err(Ok(DataError::UserNotAuthorized)));
+        let res = data_client.try_get_shared_data(&owner, &stranger, &shared_id, &share_id);
+        assert_eq!(res, Err(Ok(DataError::UserNotAuthorized)));
+
+        // Reads through the data contract land in the share's access log
+        let logs = client.get_access_logs(&owner, &share_id, &0, &10);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs.get_unchecked(0).data_ids, Vec::from_array(&env, [shared_id.clone()]));
+
+        // Revocation and expiry both close the path again
+        client.revoke_share(&owner, &share_id);
+        let res = data_client.try_get_shared_data(&owner, &recipient, &shared_id, &share_id);
+        assert_eq!(res, Err(Ok(DataError::UserNotAuthorized)));
+
+        let expiring_id = client.create_time_bound_share(&owner, &recipient, &Vec::from_array(&env, [shared_id.clone()]), &50, &60, &AccessLevel::ReadOnly);
+        env.ledger().with_mut(|li| li.timestamp = 61);
+        let res = data_client.try_get_shared_data(&owner, &recipient, &shared_id, &expiring_id);
+        assert_eq!(res, Err(Ok(DataError::UserNotAuthorized)));
     }
-} 
\ No newline at end of file
+}
@@ -1,7 +1,6 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Map, Symbol, Vec, String, Bytes, symbol_short, contracterror};
-use soroban_sdk::prng::Prng;
-use core::cmp;
+use soroban_sdk::{contract, contractclient, contractimpl, contracttype, Address, BytesN, Env, Vec, Bytes, symbol_short, contracterror};
+use soroban_sdk::xdr::ToXdr;
 
 #[contracterror]
 #[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
@@ -14,6 +13,7 @@ pub enum DataError {
     StorageError = 5, // Generic storage issue
     UserNotAuthorized = 6,
     UserNotFound = 7,
+    SharingContractNotSet = 8,
 }
 
 /// Data storage keys
@@ -24,11 +24,19 @@ pub enum DataKey {
     RegistryContract,
     UserData(Address, BytesN<32>),  // user, data_id
     DataIndex(Address),             // user -> list of data_ids
-    Permission(Address, Address)    // grantor, grantee -> AccessLevel
+    Permission(Address, Address),   // grantor, grantee -> AccessLevel
+    DataNonce,                      // Counter mixed into data IDs so they never collide
+    SharingContract,                // Data-sharing contract honoured by get_shared_data
+}
+
+/// Interface of the data-sharing contract used to authorize reads through a share
+#[contractclient(name = "SharingClient")]
+pub trait SharingInterface {
+    fn use_share(env: Env, share_id: BytesN<32>, requester: Address, data_owner: Address, data_id: BytesN<32>) -> bool;
 }
 
 /// Permission levels for data access
-#[derive(Clone, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Copy)]
 #[contracttype]
 pub enum AccessLevel {
     None = 0,
@@ -38,7 +46,7 @@ pub enum AccessLevel {
 }
 
 /// Encrypted data structure with metadata
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 #[contracttype]
 pub struct EncryptedData {
     pub encrypted_content: Bytes, // Encrypted data, format depends on client
@@ -67,17 +75,24 @@ impl DataContract {
         Ok(())
     }
     
-    /// Generate a random data ID to avoid collisions
-    fn generate_data_id(env: &Env) -> BytesN<32> {
-        // Create a random seed based on the timestamp
-        let timestamp = env.ledger().timestamp();
-        let random_bytes = timestamp.to_be_bytes();
-        
-        // Create a BytesN object from the random bytes for hashing
-        let bytes_to_hash = Bytes::from_array(env, &random_bytes);
+    /// Set the data-sharing contract whose shares can authorize reads (owner only)
+    pub fn set_sharing_contract(env: Env, sharing_contract: Address) -> Result<(), DataError> {
+        let owner = Self::get_owner(env.clone())?;
+        owner.require_auth();
+        env.storage().instance().set(&DataKey::SharingContract, &sharing_contract);
+        env.events().publish(
+            (symbol_short!("set"), symbol_short!("sharing")),
+            sharing_contract
+        );
+        Ok(())
+    }
+    
+    /// Generate a unique data ID from the user, the timestamp and a counter
+    fn generate_data_id(env: &Env, user: &Address) -> BytesN<32> {
+        let nonce = env.storage().instance().get::<DataKey, u64>(&DataKey::DataNonce).unwrap_or(0);
+        env.storage().instance().set(&DataKey::DataNonce, &(nonce + 1));
         
-        // Hash the bytes to get a deterministic but random-like BytesN<32>
-        env.crypto().sha256(&bytes_to_hash)
+        env.crypto().sha256(&(user.clone(), env.ledger().timestamp(), nonce).to_xdr(env))
     }
     
     /// Store encrypted data
@@ -90,8 +105,8 @@ impl DataContract {
     ) -> Result<BytesN<32>, DataError> {
         user.require_auth();
         
-        // Create data ID using a timestamp-based approach
-        let data_id = Self::generate_data_id(&env);
+        // Create a unique data ID
+        let data_id = Self::generate_data_id(&env, &user);
         
         // Record timestamp
         let timestamp = env.ledger().timestamp();
@@ -229,6 +244,31 @@ impl DataContract {
             return Err(DataError::UserNotAuthorized);
         }
         
+        Self::load_data(&env, data_owner, data_id)
+    }
+    
+    /// Get encrypted data by ID through an active time-bound share from the
+    /// data-sharing contract (the requester must sign reads made through a share)
+    pub fn get_shared_data(
+        env: Env,
+        data_owner: Address,
+        requester: Address,
+        data_id: BytesN<32>,
+        share_id: BytesN<32>
+    ) -> Result<EncryptedData, DataError> {
+        requester.require_auth();
+        let sharing_contract = env.storage().instance().get::<DataKey, Address>(&DataKey::SharingContract)
+            .ok_or(DataError::SharingContractNotSet)?;
+        
+        // The sharing contract checks the requester's auth, the window and the shared IDs
+        if !SharingClient::new(&env, &sharing_contract).use_share(&share_id, &requester, &data_owner, &data_id) {
+            return Err(DataError::UserNotAuthorized);
+        }
+        
+        Self::load_data(&env, data_owner, data_id)
+    }
+    
+    fn load_data(env: &Env, data_owner: Address, data_id: BytesN<32>) -> Result<EncryptedData, DataError> {
         let data = env.storage().instance().get::<DataKey, EncryptedData>(&DataKey::UserData(data_owner, data_id));
         match data {
             Some(data) => Ok(data),
@@ -236,6 +276,11 @@ impl DataContract {
         }
     }
     
+    /// Whether a data ID is stored under the given user
+    pub fn has_data(env: Env, user: Address, data_id: BytesN<32>) -> bool {
+        env.storage().instance().has(&DataKey::UserData(user, data_id))
+    }
+    
     /// List all data IDs for a user
     pub fn list_data(env: Env, user: Address) -> Result<Vec<BytesN<32>>, DataError> {
         let data_ids = env.storage().instance().get::<DataKey, Vec<BytesN<32>>>(&DataKey::DataIndex(user));
@@ -259,12 +304,11 @@ impl DataContract {
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::testutils::{Address as _, AuthorizedFunction, Events};
-    use soroban_sdk::{vec, BytesN, Env, Symbol};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::Env;
 
     fn create_test_encrypted_data(env: &Env, data_content: &[u8;32]) -> EncryptedData {
-        let mut metadata = Map::new(env);
-        metadata.set(symbol_short!("desc"), String::from_str(env, "Test data"));
+        let metadata = Bytes::from_slice(env, b"Test data");
         EncryptedData {
             encrypted_content: Bytes::from_array(env, data_content),
             metadata,
@@ -282,7 +326,7 @@ mod test {
 
         let owner = Address::generate(&env);
         let registry = Address::generate(&env);
-        client.initialize(&owner, &registry).unwrap();
+        client.initialize(&owner, &registry);
         assert_eq!(client.get_owner(), owner);
     }
 
@@ -295,22 +339,22 @@ mod test {
 
         let owner = Address::generate(&env);
         let registry = Address::generate(&env);
-        client.initialize(&owner, &registry).unwrap();
+        client.initialize(&owner, &registry);
 
         let user = Address::generate(&env);
         let test_data_content = [1u8; 32];
         let encrypted_data_struct = create_test_encrypted_data(&env, &test_data_content);
 
-        let data_id = client.store_data(&user, &encrypted_data_struct.encrypted_content, &encrypted_data_struct.metadata, encrypted_data_struct.data_type).unwrap();
+        let data_id = client.store_data(&user, &encrypted_data_struct.encrypted_content, &encrypted_data_struct.metadata, &encrypted_data_struct.data_type);
         
-        let retrieved_data = client.get_data(&user, &user, &data_id).unwrap();
+        let retrieved_data = client.get_data(&user, &user, &data_id);
         assert_eq!(retrieved_data.encrypted_content, encrypted_data_struct.encrypted_content);
         assert_eq!(retrieved_data.metadata, encrypted_data_struct.metadata);
         assert_eq!(retrieved_data.data_type, encrypted_data_struct.data_type);
 
         let data_list = client.list_data(&user);
         assert_eq!(data_list.len(), 1);
-        assert_eq!(data_list.get(0).unwrap().unwrap(), data_id);
+        assert_eq!(data_list.get(0).unwrap(), data_id);
     }
 
     #[test]
@@ -322,31 +366,31 @@ mod test {
 
         let owner = Address::generate(&env);
         let registry = Address::generate(&env);
-        client.initialize(&owner, &registry).unwrap();
+        client.initialize(&owner, &registry);
 
         let user1 = Address::generate(&env);
         let user2 = Address::generate(&env);
         let test_data_content = [2u8; 32];
         let encrypted_data_struct = create_test_encrypted_data(&env, &test_data_content);
 
-        let data_id = client.store_data(&user1, &encrypted_data_struct.encrypted_content, &encrypted_data_struct.metadata, encrypted_data_struct.data_type).unwrap();
+        let data_id = client.store_data(&user1, &encrypted_data_struct.encrypted_content, &encrypted_data_struct.metadata, &encrypted_data_struct.data_type);
 
         // user2 should not have access initially
-        assert_eq!(client.check_permission_internal(&user1, &user2).unwrap(), AccessLevel::None);
-        let get_res = client.get_data(&user1, &user2, &data_id);
-        assert!(get_res.is_err());
+        assert_eq!(client.check_permission_internal(&user1, &user2), AccessLevel::None);
+        let get_res = client.try_get_data(&user1, &user2, &data_id);
+        assert_eq!(get_res, Err(Ok(DataError::UserNotAuthorized)));
 
         // Grant read access
-        client.grant_permission(&user1, &user2, &AccessLevel::ReadOnly).unwrap();
-        assert_eq!(client.check_permission_internal(&user1, &user2).unwrap(), AccessLevel::ReadOnly);
+        client.grant_permission(&user1, &user2, &AccessLevel::ReadOnly);
+        assert_eq!(client.check_permission_internal(&user1, &user2), AccessLevel::ReadOnly);
         
-        let retrieved_data = client.get_data(&user1, &user2, &data_id).unwrap();
+        let retrieved_data = client.get_data(&user1, &user2, &data_id);
         assert_eq!(retrieved_data.encrypted_content, encrypted_data_struct.encrypted_content);
         assert_eq!(retrieved_data.metadata, encrypted_data_struct.metadata);
         assert_eq!(retrieved_data.data_type, encrypted_data_struct.data_type);
 
         // User1 (owner) should have ReadWrite
-        assert_eq!(client.check_permission_internal(&user1, &user1).unwrap(), AccessLevel::ReadWrite);
+        assert_eq!(client.check_permission_internal(&user1, &user1), AccessLevel::ReadWrite);
     }
 
     #[test]
@@ -357,15 +401,16 @@ mod test {
         let client = DataContractClient::new(&env, &contract_id);
 
         let owner = Address::generate(&env);
-        client.initialize(&owner, &Address::generate(&env)).unwrap();
+        client.initialize(&owner, &Address::generate(&env));
         let user = Address::generate(&env);
         let encrypted_data_struct = create_test_encrypted_data(&env, &[3u8; 32]);
-        let data_id = client.store_data(&user, &encrypted_data_struct.encrypted_content, &encrypted_data_struct.metadata, encrypted_data_struct.data_type).unwrap();
+        let data_id = client.store_data(&user, &encrypted_data_struct.encrypted_content, &encrypted_data_struct.metadata, &encrypted_data_struct.data_type);
 
         assert_eq!(client.list_data(&user).len(), 1);
-        client.delete_data(&user, &data_id).unwrap();
+        client.delete_data(&user, &data_id);
         assert_eq!(client.list_data(&user).len(), 0);
-        let get_res = client.get_data(&user, &user, &data_id);
-        assert!(get_res.is_err());
+        let get_res = client.try_get_data(&user, &user, &data_id);
+        assert_eq!(get_res, Err(Ok(DataError::DataNotFound)));
+        assert!(!client.has_data(&user, &data_id));
     }
 } 
\ No newline at end of file
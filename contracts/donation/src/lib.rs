@@ -1,252 +1,1626 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Map, String, Symbol, Vec};
+use soroban_sdk::{contract, contractclient, contracterror, contractimpl, contracttype, symbol_short, token, Address, BytesN, Env, String, Vec};
+use soroban_sdk::xdr::ToXdr;
+
+#[contracterror]
+#[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum DonationError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    Unauthorized = 3,
+    InitiativeNotFound = 4,
+    InvalidAmount = 5,
+    NoSwapRoute = 6,
+    SlippageExceeded = 7,
+    AdapterAlreadyRegistered = 8,
+    AdapterNotFound = 9,
+    InvalidMilestones = 10,
+    DeadlinePassed = 11,
+    GoalNotReached = 12,
+    MilestoneNotFound = 13,
+    MilestoneAlreadyReleased = 14,
+    RefundNotAvailable = 15,
+    NothingToRefund = 16,
+    PledgeNotFound = 17,
+    InvalidPledge = 18,
+    InvalidPledgeState = 19,
+    ReceiptNotFound = 20,
+    InvalidStatusTransition = 21,
+    InitiativeNotActive = 22,
+    NoPendingRecipient = 23,
+    TimelockNotExpired = 24,
+}
+
+// Length of a reporting period for per-initiative totals (30 days)
+pub const REPORT_PERIOD: u64 = 2_592_000;
+// Maximum number of periods returned by one export call
+pub const MAX_REPORT_PERIODS: u64 = 24;
+// Delay before a proposed recipient change can take effect (2 days)
+pub const RECIPIENT_TIMELOCK: u64 = 172_800;
+// Maximum number of initiatives returned by one listing call
+pub const MAX_PAGE_SIZE: u32 = 50;
+// Donations stored per page of a donor's history
+pub const DONATION_PAGE_SIZE: u32 = 50;
+// IDs stored per page of an ID list
+pub const ID_PAGE_SIZE: u32 = 50;
+
+// Storage keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Admin,                  // Address allowed to manage initiatives
+    NextInitiativeId,       // Next initiative ID to hand out
+    Initiative(u32),        // Initiative ID -> Initiative (persistent)
+    DonationCount(Address), // Donor -> number of donations in their history (persistent)
+    DonationPage(Address, u32), // (Donor, page number) -> Vec<Donation> of up to DONATION_PAGE_SIZE (persistent)
+    SwapAdapters,           // Vec<Address> of registered swap adapters
+    Oracle,                 // Address allowed to approve milestone releases alongside the admin
+    Contribution(u32, DonorRef), // (Initiative ID, donor) -> escrowed amount (persistent)
+    NextPledgeId,           // Next pledge ID to hand out
+    Pledge(u32),            // Pledge ID -> Pledge (persistent)
+    PledgeCursor,           // Position in IdList::ActivePledges where the next keeper run starts
+    DonorPledges(Address),  // Donor -> Vec<u32> of pledge IDs (persistent)
+    NextReceiptId,          // Next receipt ID to hand out
+    Receipt(u64),           // Receipt ID -> Receipt (persistent)
+    PeriodTotal(u32, u64),  // (Initiative ID, period index) -> PeriodTotal (persistent)
+    PendingRecipient(u32),  // Initiative ID -> PendingRecipient awaiting its timelock (persistent)
+    IdCount(IdList),        // List -> number of IDs in it (persistent)
+    IdPage(IdList, u32),    // (List, page number) -> Vec<u32> of up to ID_PAGE_SIZE IDs (persistent)
+    IdPosition(IdList, u32), // (List, ID) -> position of the ID in the list (persistent)
+}
+
+// Unordered ID lists, paged so no entry grows without bound. Removing an ID moves
+// the last one into its place.
+#[derive(Clone)]
+#[contracttype]
+pub enum IdList {
+    ActivePledges,          // Pledges the keeper should scan
+    InitiativesByStatus(InitiativeStatus), // Initiatives currently in a status
+}
+
+// Initiatives start as drafts and only accept funds once verified and activated
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum InitiativeStatus {
+    Draft,
+    Verified,
+    Active,
+    Paused,
+    Closed,
+}
+
+// Recipient change proposed by the admin, applicable once effective_at is reached
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingRecipient {
+    pub recipient: Address,
+    pub effective_at: u64,
+}
 
 #[derive(Clone)]
 #[contracttype]
 pub struct Initiative {
-    name: String,
-    description: String,
-    recipient: Address,
-    preferred_asset: Asset,
-    raised_amount: i128,
+    pub name: String,
+    pub description: String,
+    pub recipient: Address,
+    pub preferred_asset: Address, // Token contract the recipient wants to receive
+    pub raised_amount: i128,
+    pub goal: i128,               // Amount that must be raised before any milestone is released
+    pub deadline: u64,            // Ledger timestamp after which donations close
+    pub milestones: Vec<Milestone>,
+    pub released_amount: i128,    // Escrowed funds already paid out to the recipient
+    pub status: InitiativeStatus,
+}
+
+// A tranche of escrowed funds paid to the recipient once approved
+#[derive(Clone)]
+#[contracttype]
+pub struct Milestone {
+    pub description: String,
+    pub amount: i128,
+    pub released: bool,
+}
+
+// Who a donation is attributed to. Anonymous donations only expose
+// sha256(donor XDR || salt) in contract storage and events, which the donor can reproduce
+// to prove a receipt is theirs. See donate_with_path_payment for what this does not hide.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum DonorRef {
+    Public(Address),
+    Anonymous(BytesN<32>),
 }
 
 #[derive(Clone)]
 #[contracttype]
 pub struct Donation {
-    donor: Address,
-    initiative_id: u32,
-    from_asset: Asset,
-    to_asset: Asset,
-    amount: i128,
-    converted_amount: i128,
-    timestamp: u64,
+    pub donor: DonorRef,
+    pub receipt_id: u64,
+    pub initiative_id: u32,
+    pub from_asset: Address,
+    pub to_asset: Address,
+    pub amount: i128,
+    pub converted_amount: i128,
+    pub timestamp: u64,
 }
 
+// Record of what an initiative's escrow received, usable as a donation receipt
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Receipt {
+    pub receipt_id: u64,
+    pub initiative_id: u32,
+    pub donor: DonorRef,
+    pub amount: i128,
+    pub asset: Address,
+    pub timestamp: u64,
+}
+
+// Donations received by an initiative during one reporting period
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PeriodTotal {
+    pub period_start: u64,
+    pub amount: i128,
+    pub donation_count: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum PledgeStatus {
+    Active,
+    Paused,
+    Cancelled,
+    Completed,
+}
+
+// A recurring donation in the initiative's preferred asset, pulled through a token allowance
+// the donor grants this contract with `approve`
 #[derive(Clone)]
 #[contracttype]
-pub struct Asset {
-    code: String,
-    issuer: Option<Address>,
+pub struct Pledge {
+    pub donor: Address,
+    pub initiative_id: u32,
+    pub amount: i128,
+    pub interval: u64,        // Seconds between executions
+    pub max_count: u32,
+    pub executed_count: u32,
+    pub next_due: u64,        // Ledger timestamp of the next execution
+    pub status: PledgeStatus,
 }
 
-#[contract]
-pub struct DonationContract {
-    initiatives: Map<u32, Initiative>,
-    donations: Map<Address, Vec<Donation>>,
-    total_donated: Map<u32, i128>,
-    next_initiative_id: u32,
+// Cross-contract interface a DEX/AMM adapter must expose to convert donations.
+// `swap` pulls `amount_in` of `from_asset` from `payer` and sends at least `min_out`
+// of `to_asset` to `to`, returning the amount sent.
+#[contractclient(name = "SwapAdapterClient")]
+pub trait SwapAdapter {
+    fn quote(env: Env, from_asset: Address, to_asset: Address, amount_in: i128) -> i128;
+    fn swap(env: Env, payer: Address, from_asset: Address, to_asset: Address, amount_in: i128, min_out: i128, to: Address) -> i128;
 }
 
+#[contract]
+pub struct DonationContract;
+
 #[contractimpl]
 impl DonationContract {
     // Initialize the contract
-    pub fn initialize(env: Env, admin: Address) -> Self {
-        admin.require_auth();
-        
-        Self {
-            initiatives: Map::new(&env),
-            donations: Map::new(&env),
-            total_donated: Map::new(&env),
-            next_initiative_id: 1,
+    pub fn initialize(env: Env, admin: Address) -> Result<(), DonationError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(DonationError::AlreadyInitialized);
         }
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::NextInitiativeId, &1u32);
+
+        env.events().publish(
+            (symbol_short!("init"), symbol_short!("donation")),
+            admin
+        );
+
+        Ok(())
     }
-    
-    // Create a new health initiative
+
+    // Create a new health initiative (admin only). Milestone amounts must add up to the goal.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_initiative(
         env: Env,
         admin: Address,
         name: String,
         description: String,
         recipient: Address,
-        preferred_asset: Asset,
-    ) -> u32 {
-        admin.require_auth();
-        
-        let contract = Self::load_contract(&env);
-        let id = contract.next_initiative_id;
-        let mut contract = contract;
-        contract.next_initiative_id += 1;
-        
+        preferred_asset: Address,
+        goal: i128,
+        deadline: u64,
+        milestones: Vec<Milestone>,
+    ) -> Result<u32, DonationError> {
+        Self::require_admin(&env, &admin)?;
+
+        if goal <= 0 || deadline <= env.ledger().timestamp() {
+            return Err(DonationError::InvalidAmount);
+        }
+
+        let mut total: i128 = 0;
+        let mut pending_milestones = Vec::new(&env);
+        for mut milestone in milestones.iter() {
+            if milestone.amount <= 0 {
+                return Err(DonationError::InvalidMilestones);
+            }
+            total += milestone.amount;
+            milestone.released = false;
+            pending_milestones.push_back(milestone);
+        }
+        if pending_milestones.is_empty() || total != goal {
+            return Err(DonationError::InvalidMilestones);
+        }
+
+        let id = env.storage().instance().get::<DataKey, u32>(&DataKey::NextInitiativeId).unwrap_or(1);
+        env.storage().instance().set(&DataKey::NextInitiativeId, &(id + 1));
+
         let initiative = Initiative {
             name,
             description,
             recipient,
             preferred_asset,
             raised_amount: 0,
+            goal,
+            deadline,
+            milestones: pending_milestones,
+            released_amount: 0,
+            status: InitiativeStatus::Draft,
         };
-        
-        contract.initiatives.set(id, initiative);
-        contract.total_donated.set(id, 0);
-        
-        let env = env.clone();
-        Self::save_contract(&env, &contract);
-        
-        id
-    }
-    
-    // Donate to an initiative (in production this would call a path payment operation)
+        env.storage().persistent().set(&DataKey::Initiative(id), &initiative);
+        Self::index_status(&env, InitiativeStatus::Draft, id, true);
+
+        env.events().publish(
+            (symbol_short!("create"), symbol_short!("init")),
+            id
+        );
+
+        Ok(id)
+    }
+
+    // Donate to an initiative, converting from_asset into the preferred asset through the
+    // best-quoting registered swap adapter. Funds are held in escrow until milestones are
+    // released. Reverts if escrow receives less than min_received. Passing a salt makes the
+    // donation anonymous: public history, receipts and events only carry the donor commitment.
+    // This only hides the donor from contract-level listings. The transaction still carries the
+    // donor's authorization and the salt, and the token's transfer event names the donor, so
+    // anyone reading the ledger can link the donation to the address.
+    // Returns the receipt ID.
     pub fn donate_with_path_payment(
         env: Env,
         donor: Address,
         initiative_id: u32,
-        from_asset: Asset,
+        from_asset: Address,
+        amount: i128,
+        min_received: i128,
+        anonymous_salt: Option<BytesN<32>>,
+    ) -> Result<u64, DonationError> {
+        donor.require_auth();
+
+        if amount <= 0 {
+            return Err(DonationError::InvalidAmount);
+        }
+
+        let mut initiative = Self::get_initiative(env.clone(), initiative_id)?;
+        if initiative.status != InitiativeStatus::Active {
+            return Err(DonationError::InitiativeNotActive);
+        }
+        if env.ledger().timestamp() > initiative.deadline {
+            return Err(DonationError::DeadlinePassed);
+        }
+
+        // Record what escrow actually received rather than the requested amount
+        let escrow = env.current_contract_address();
+        let to_token = token::Client::new(&env, &initiative.preferred_asset);
+        let balance_before = to_token.balance(&escrow);
+
+        if from_asset == initiative.preferred_asset {
+            to_token.transfer(&donor, &escrow, &amount);
+        } else {
+            let adapter = Self::best_adapter(&env, &from_asset, &initiative.preferred_asset, amount)?;
+            SwapAdapterClient::new(&env, &adapter).swap(
+                &donor,
+                &from_asset,
+                &initiative.preferred_asset,
+                &amount,
+                &min_received,
+                &escrow,
+            );
+        }
+
+        // Adapters are external code, so re-check slippage against the real balance change
+        let converted_amount = to_token.balance(&escrow) - balance_before;
+        if converted_amount < min_received {
+            return Err(DonationError::SlippageExceeded);
+        }
+
+        let donor_ref = match anonymous_salt {
+            Some(salt) => DonorRef::Anonymous(Self::donor_commitment(&env, &donor, &salt)),
+            None => DonorRef::Public(donor),
+        };
+
+        Ok(Self::record_donation(&env, donor_ref, initiative_id, &mut initiative, from_asset, amount, converted_amount))
+    }
+
+    // Get a user's donations, oldest first, starting at index `cursor`
+    pub fn get_donation_history(env: Env, user: Address, cursor: u32, limit: u32) -> Vec<Donation> {
+        let count = env.storage().persistent().get::<DataKey, u32>(&DataKey::DonationCount(user.clone())).unwrap_or(0);
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+
+        let mut donations = Vec::new(&env);
+        let mut i = cursor;
+        while i < end {
+            let page_number = i / DONATION_PAGE_SIZE;
+            let page_end = ((page_number + 1) * DONATION_PAGE_SIZE).min(end);
+            let page = env.storage().persistent().get::<DataKey, Vec<Donation>>(&DataKey::DonationPage(user.clone(), page_number)).unwrap_or(Vec::new(&env));
+            for j in i..page_end {
+                if let Some(donation) = page.get(j % DONATION_PAGE_SIZE) {
+                    donations.push_back(donation);
+                }
+            }
+            i = page_end;
+        }
+        donations
+    }
+
+    // Get the number of donations in a user's history
+    pub fn get_donation_count(env: Env, user: Address) -> u32 {
+        env.storage().persistent().get::<DataKey, u32>(&DataKey::DonationCount(user)).unwrap_or(0)
+    }
+
+    // Get initiative details
+    pub fn get_initiative(env: Env, initiative_id: u32) -> Result<Initiative, DonationError> {
+        match env.storage().persistent().get::<DataKey, Initiative>(&DataKey::Initiative(initiative_id)) {
+            Some(initiative) => Ok(initiative),
+            None => Err(DonationError::InitiativeNotFound),
+        }
+    }
+
+    // Get a page of initiatives in creation order
+    pub fn list_initiatives(env: Env, cursor: u32, limit: u32) -> Vec<(u32, Initiative)> {
+        let next_id = env.storage().instance().get::<DataKey, u32>(&DataKey::NextInitiativeId).unwrap_or(1);
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(next_id - 1);
+        let mut result = Vec::new(&env);
+
+        for id in (cursor + 1)..=end {
+            if let Some(initiative) = env.storage().persistent().get::<DataKey, Initiative>(&DataKey::Initiative(id)) {
+                result.push_back((id, initiative));
+            }
+        }
+
+        result
+    }
+
+    // Get a page of initiatives currently in the given status. Initiatives are listed in
+    // the order they entered it until one leaves, which moves the newest into its place.
+    pub fn list_initiatives_by_status(env: Env, status: InitiativeStatus, cursor: u32, limit: u32) -> Vec<(u32, Initiative)> {
+        let list = IdList::InitiativesByStatus(status);
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(Self::id_count(&env, &list));
+        let mut result = Vec::new(&env);
+
+        for id in Self::id_range(&env, &list, cursor, end).iter() {
+            if let Some(initiative) = env.storage().persistent().get::<DataKey, Initiative>(&DataKey::Initiative(id)) {
+                result.push_back((id, initiative));
+            }
+        }
+
+        result
+    }
+
+    // Mark a draft initiative as verified (admin only)
+    pub fn verify_initiative(env: Env, admin: Address, initiative_id: u32) -> Result<(), DonationError> {
+        Self::transition(&env, &admin, initiative_id, &[InitiativeStatus::Draft], InitiativeStatus::Verified)
+    }
+
+    // Open a verified or paused initiative for donations (admin only)
+    pub fn activate_initiative(env: Env, admin: Address, initiative_id: u32) -> Result<(), DonationError> {
+        Self::transition(&env, &admin, initiative_id, &[InitiativeStatus::Verified, InitiativeStatus::Paused], InitiativeStatus::Active)
+    }
+
+    // Temporarily stop donations and milestone releases (admin only)
+    pub fn pause_initiative(env: Env, admin: Address, initiative_id: u32) -> Result<(), DonationError> {
+        Self::transition(&env, &admin, initiative_id, &[InitiativeStatus::Active], InitiativeStatus::Paused)
+    }
+
+    // Permanently close an initiative; if its goal was missed donors can claim refunds (admin only)
+    pub fn close_initiative(env: Env, admin: Address, initiative_id: u32) -> Result<(), DonationError> {
+        Self::transition(
+            &env,
+            &admin,
+            initiative_id,
+            &[InitiativeStatus::Draft, InitiativeStatus::Verified, InitiativeStatus::Active, InitiativeStatus::Paused],
+            InitiativeStatus::Closed,
+        )
+    }
+
+    // Edit an initiative's name and description (admin only, not once closed)
+    pub fn update_initiative(env: Env, admin: Address, initiative_id: u32, name: String, description: String) -> Result<(), DonationError> {
+        Self::require_admin(&env, &admin)?;
+
+        let mut initiative = Self::get_initiative(env.clone(), initiative_id)?;
+        if initiative.status == InitiativeStatus::Closed {
+            return Err(DonationError::InvalidStatusTransition);
+        }
+
+        initiative.name = name;
+        initiative.description = description;
+        env.storage().persistent().set(&DataKey::Initiative(initiative_id), &initiative);
+
+        env.events().publish(
+            (symbol_short!("update"), initiative_id),
+            initiative.status
+        );
+
+        Ok(())
+    }
+
+    // Propose a new recipient, applicable after RECIPIENT_TIMELOCK (admin only)
+    pub fn propose_recipient_change(env: Env, admin: Address, initiative_id: u32, recipient: Address) -> Result<u64, DonationError> {
+        Self::require_admin(&env, &admin)?;
+
+        let initiative = Self::get_initiative(env.clone(), initiative_id)?;
+        if initiative.status == InitiativeStatus::Closed {
+            return Err(DonationError::InvalidStatusTransition);
+        }
+
+        let effective_at = env.ledger().timestamp() + RECIPIENT_TIMELOCK;
+        let pending = PendingRecipient {
+            recipient: recipient.clone(),
+            effective_at,
+        };
+        env.storage().persistent().set(&DataKey::PendingRecipient(initiative_id), &pending);
+
+        env.events().publish(
+            (symbol_short!("recip"), symbol_short!("propose"), initiative_id),
+            (recipient, effective_at)
+        );
+
+        Ok(effective_at)
+    }
+
+    // Withdraw a pending recipient change (admin only)
+    pub fn cancel_recipient_change(env: Env, admin: Address, initiative_id: u32) -> Result<(), DonationError> {
+        Self::require_admin(&env, &admin)?;
+        Self::get_pending_recipient(env.clone(), initiative_id)?;
+        env.storage().persistent().remove(&DataKey::PendingRecipient(initiative_id));
+
+        env.events().publish(
+            (symbol_short!("recip"), symbol_short!("cancel"), initiative_id),
+            ()
+        );
+
+        Ok(())
+    }
+
+    // Apply a pending recipient change once its timelock has expired. Anyone may call this.
+    pub fn apply_recipient_change(env: Env, initiative_id: u32) -> Result<(), DonationError> {
+        let pending = Self::get_pending_recipient(env.clone(), initiative_id)?;
+        if env.ledger().timestamp() < pending.effective_at {
+            return Err(DonationError::TimelockNotExpired);
+        }
+
+        let mut initiative = Self::get_initiative(env.clone(), initiative_id)?;
+        if initiative.status == InitiativeStatus::Closed {
+            return Err(DonationError::InvalidStatusTransition);
+        }
+
+        initiative.recipient = pending.recipient.clone();
+        env.storage().persistent().set(&DataKey::Initiative(initiative_id), &initiative);
+        env.storage().persistent().remove(&DataKey::PendingRecipient(initiative_id));
+
+        env.events().publish(
+            (symbol_short!("recip"), symbol_short!("apply"), initiative_id),
+            pending.recipient
+        );
+
+        Ok(())
+    }
+
+    // Get the pending recipient change for an initiative
+    pub fn get_pending_recipient(env: Env, initiative_id: u32) -> Result<PendingRecipient, DonationError> {
+        env.storage().persistent().get::<DataKey, PendingRecipient>(&DataKey::PendingRecipient(initiative_id)).ok_or(DonationError::NoPendingRecipient)
+    }
+
+    // Get total donated to an initiative
+    pub fn get_total_donated(env: Env, initiative_id: u32) -> i128 {
+        Self::get_initiative(env, initiative_id).map(|initiative| initiative.raised_amount).unwrap_or(0)
+    }
+
+    // Create a recurring pledge; the first execution is due immediately
+    pub fn create_pledge(
+        env: Env,
+        donor: Address,
+        initiative_id: u32,
         amount: i128,
-    ) -> Result<(), String> {
+        interval: u64,
+        max_count: u32,
+    ) -> Result<u32, DonationError> {
         donor.require_auth();
-        
-        let contract = Self::load_contract(&env);
-        
-        // Check if initiative exists
-        let initiative = contract.initiatives.get(initiative_id)
-            .ok_or(String::from_str(&env, "Initiative not found"))?;
-        
-        // In a real implementation, this would:
-        // 1. Create a path payment operation from from_asset to initiative.preferred_asset
-        // 2. Route the payment through the Stellar DEX for best conversion rate
-        
-        // For prototype purposes, we're simulating the conversion
-        // Assume a simple 1:1 conversion ratio for demo (would be based on actual DEX rates)
-        let converted_amount = amount;
-        
-        // Record the donation
-        let donation = Donation {
+
+        if amount <= 0 || interval == 0 || max_count == 0 {
+            return Err(DonationError::InvalidPledge);
+        }
+
+        let initiative = Self::get_initiative(env.clone(), initiative_id)?;
+        if initiative.status != InitiativeStatus::Active {
+            return Err(DonationError::InitiativeNotActive);
+        }
+        if env.ledger().timestamp() > initiative.deadline {
+            return Err(DonationError::DeadlinePassed);
+        }
+
+        let id = env.storage().instance().get::<DataKey, u32>(&DataKey::NextPledgeId).unwrap_or(1);
+        env.storage().instance().set(&DataKey::NextPledgeId, &(id + 1));
+
+        let pledge = Pledge {
             donor: donor.clone(),
             initiative_id,
-            from_asset,
-            to_asset: initiative.preferred_asset.clone(),
             amount,
-            converted_amount,
-            timestamp: env.ledger().timestamp(),
+            interval,
+            max_count,
+            executed_count: 0,
+            next_due: env.ledger().timestamp(),
+            status: PledgeStatus::Active,
         };
-        
-        // Update user's donation history
-        let mut contract = contract;
-        let mut user_donations = contract.donations.get(donor.clone()).unwrap_or(Vec::new(&env));
-        user_donations.push_back(donation);
-        contract.donations.set(donor, user_donations);
-        
-        // Update initiative's total raised amount
-        let current_total = contract.total_donated.get(initiative_id).unwrap_or(0);
-        contract.total_donated.set(initiative_id, current_total + converted_amount);
-        
-        // Update initiative raised amount
-        let mut updated_initiative = initiative;
-        updated_initiative.raised_amount += converted_amount;
-        contract.initiatives.set(initiative_id, updated_initiative);
-        
-        let env = env.clone();
-        Self::save_contract(&env, &contract);
-        
-        // In production, we would return the transaction hash for the path payment
+        env.storage().persistent().set(&DataKey::Pledge(id), &pledge);
+
+        let mut donor_pledges = env.storage().persistent().get::<DataKey, Vec<u32>>(&DataKey::DonorPledges(donor.clone())).unwrap_or(Vec::new(&env));
+        donor_pledges.push_back(id);
+        env.storage().persistent().set(&DataKey::DonorPledges(donor.clone()), &donor_pledges);
+
+        Self::set_pledge_active(&env, id, true);
+
+        env.events().publish(
+            (symbol_short!("pledge"), symbol_short!("create")),
+            (id, donor)
+        );
+
+        Ok(id)
+    }
+
+    // Pause an active pledge (donor only)
+    pub fn pause_pledge(env: Env, donor: Address, pledge_id: u32) -> Result<(), DonationError> {
+        let mut pledge = Self::get_own_pledge(&env, &donor, pledge_id)?;
+        if pledge.status != PledgeStatus::Active {
+            return Err(DonationError::InvalidPledgeState);
+        }
+
+        pledge.status = PledgeStatus::Paused;
+        env.storage().persistent().set(&DataKey::Pledge(pledge_id), &pledge);
+        Self::set_pledge_active(&env, pledge_id, false);
+
+        env.events().publish(
+            (symbol_short!("pledge"), symbol_short!("pause")),
+            pledge_id
+        );
+
         Ok(())
     }
-    
-    // Get donation history for a user
-    pub fn get_donation_history(env: Env, user: Address) -> Vec<Donation> {
-        let contract = Self::load_contract(&env);
-        contract.donations.get(user).unwrap_or(Vec::new(&env))
+
+    // Resume a paused pledge; missed cycles are skipped rather than charged in a burst
+    pub fn resume_pledge(env: Env, donor: Address, pledge_id: u32) -> Result<(), DonationError> {
+        let mut pledge = Self::get_own_pledge(&env, &donor, pledge_id)?;
+        if pledge.status != PledgeStatus::Paused {
+            return Err(DonationError::InvalidPledgeState);
+        }
+
+        pledge.status = PledgeStatus::Active;
+        pledge.next_due = pledge.next_due.max(env.ledger().timestamp());
+        env.storage().persistent().set(&DataKey::Pledge(pledge_id), &pledge);
+        Self::set_pledge_active(&env, pledge_id, true);
+
+        env.events().publish(
+            (symbol_short!("pledge"), symbol_short!("resume")),
+            pledge_id
+        );
+
+        Ok(())
     }
-    
-    // Get initiative details
-    pub fn get_initiative(env: Env, initiative_id: u32) -> Option<Initiative> {
-        let contract = Self::load_contract(&env);
-        contract.initiatives.get(initiative_id)
-    }
-    
-    // Get all initiatives
-    pub fn list_initiatives(env: Env) -> Vec<(u32, Initiative)> {
-        let contract = Self::load_contract(&env);
+
+    // Cancel an active or paused pledge (donor only)
+    pub fn cancel_pledge(env: Env, donor: Address, pledge_id: u32) -> Result<(), DonationError> {
+        let mut pledge = Self::get_own_pledge(&env, &donor, pledge_id)?;
+        if pledge.status != PledgeStatus::Active && pledge.status != PledgeStatus::Paused {
+            return Err(DonationError::InvalidPledgeState);
+        }
+
+        pledge.status = PledgeStatus::Cancelled;
+        env.storage().persistent().set(&DataKey::Pledge(pledge_id), &pledge);
+        Self::set_pledge_active(&env, pledge_id, false);
+
+        env.events().publish(
+            (symbol_short!("pledge"), symbol_short!("cancel")),
+            pledge_id
+        );
+
+        Ok(())
+    }
+
+    // Scan up to `limit` active pledges and execute those that are due; returns how many ran.
+    // Permissionless so any keeper can drive it. Each run resumes where the last one stopped,
+    // so a large scan list is covered over several calls. Pledges whose transfer fails
+    // (short allowance or balance, frozen account) are skipped until the next run.
+    pub fn process_due_pledges(env: Env, limit: u32) -> u32 {
+        let now = env.ledger().timestamp();
+        let escrow = env.current_contract_address();
+        let mut executed = 0;
+
+        let mut len = Self::id_count(&env, &IdList::ActivePledges);
+        if len == 0 {
+            return 0;
+        }
+        let mut pos = env.storage().instance().get::<DataKey, u32>(&DataKey::PledgeCursor).unwrap_or(0) % len;
+
+        for _ in 0..limit.min(len) {
+            if len == 0 {
+                break;
+            }
+            if pos >= len {
+                pos = 0;
+            }
+            let pledge_id = match Self::id_at(&env, &IdList::ActivePledges, pos) {
+                Some(pledge_id) => pledge_id,
+                None => break,
+            };
+            // Ending a pledge moves the last one into its position, which is scanned next
+            let mut pledge = match env.storage().persistent().get::<DataKey, Pledge>(&DataKey::Pledge(pledge_id)) {
+                Some(pledge) => pledge,
+                None => {
+                    pos += 1;
+                    continue;
+                }
+            };
+            if pledge.next_due > now {
+                pos += 1;
+                continue;
+            }
+
+            let mut initiative = match Self::get_initiative(env.clone(), pledge.initiative_id) {
+                Ok(initiative) => initiative,
+                Err(_) => {
+                    pos += 1;
+                    continue;
+                }
+            };
+
+            // Donations close with the initiative or at its deadline, so the pledge ends with them
+            if now > initiative.deadline || initiative.status == InitiativeStatus::Closed {
+                pledge.status = PledgeStatus::Cancelled;
+                env.storage().persistent().set(&DataKey::Pledge(pledge_id), &pledge);
+                Self::set_pledge_active(&env, pledge_id, false);
+                len -= 1;
+                continue;
+            }
+            if initiative.status != InitiativeStatus::Active {
+                pos += 1;
+                continue;
+            }
+
+            // A failed transfer only skips this pledge instead of reverting the whole run
+            let token_client = token::Client::new(&env, &initiative.preferred_asset);
+            let balance_before = token_client.balance(&escrow);
+            if !matches!(token_client.try_transfer_from(&escrow, &pledge.donor, &escrow, &pledge.amount), Ok(Ok(()))) {
+                env.events().publish(
+                    (symbol_short!("pledge"), symbol_short!("skipped")),
+                    pledge_id
+                );
+                pos += 1;
+                continue;
+            }
+            let received = token_client.balance(&escrow) - balance_before;
+
+            let asset = initiative.preferred_asset.clone();
+            Self::record_donation(&env, DonorRef::Public(pledge.donor.clone()), pledge.initiative_id, &mut initiative, asset, pledge.amount, received);
+
+            pledge.executed_count += 1;
+            pledge.next_due = now + pledge.interval;
+            executed += 1;
+            if pledge.executed_count >= pledge.max_count {
+                pledge.status = PledgeStatus::Completed;
+                env.storage().persistent().set(&DataKey::Pledge(pledge_id), &pledge);
+                Self::set_pledge_active(&env, pledge_id, false);
+                len -= 1;
+                continue;
+            }
+            env.storage().persistent().set(&DataKey::Pledge(pledge_id), &pledge);
+            pos += 1;
+        }
+
+        // Resume at the first pledge this run did not reach
+        let next = if pos < len { pos } else { 0 };
+        env.storage().instance().set(&DataKey::PledgeCursor, &next);
+
+        executed
+    }
+
+    // Get pledge details
+    pub fn get_pledge(env: Env, pledge_id: u32) -> Result<Pledge, DonationError> {
+        env.storage().persistent().get::<DataKey, Pledge>(&DataKey::Pledge(pledge_id)).ok_or(DonationError::PledgeNotFound)
+    }
+
+    // Get a donor's pledge IDs
+    pub fn list_pledges(env: Env, donor: Address) -> Vec<u32> {
+        env.storage().persistent().get::<DataKey, Vec<u32>>(&DataKey::DonorPledges(donor)).unwrap_or(Vec::new(&env))
+    }
+
+    // Set the oracle that may approve milestone releases (admin only)
+    pub fn set_oracle(env: Env, admin: Address, oracle: Address) -> Result<(), DonationError> {
+        Self::require_admin(&env, &admin)?;
+        env.storage().instance().set(&DataKey::Oracle, &oracle);
+
+        env.events().publish(
+            (symbol_short!("set"), symbol_short!("oracle")),
+            oracle
+        );
+
+        Ok(())
+    }
+
+    // Release a milestone's escrowed funds to the recipient (admin or oracle).
+    // The final milestone also sweeps anything raised beyond the goal.
+    pub fn release_milestone(env: Env, approver: Address, initiative_id: u32, milestone_index: u32) -> Result<i128, DonationError> {
+        let admin = Self::get_admin_internal(&env)?;
+        let oracle = env.storage().instance().get::<DataKey, Address>(&DataKey::Oracle);
+        if approver != admin && Some(approver.clone()) != oracle {
+            return Err(DonationError::Unauthorized);
+        }
+        approver.require_auth();
+
+        let mut initiative = Self::get_initiative(env.clone(), initiative_id)?;
+        if initiative.status == InitiativeStatus::Paused {
+            return Err(DonationError::InitiativeNotActive);
+        }
+        if initiative.raised_amount < initiative.goal {
+            return Err(DonationError::GoalNotReached);
+        }
+
+        let mut milestone = initiative.milestones.get(milestone_index).ok_or(DonationError::MilestoneNotFound)?;
+        if milestone.released {
+            return Err(DonationError::MilestoneAlreadyReleased);
+        }
+
+        milestone.released = true;
+        initiative.milestones.set(milestone_index, milestone.clone());
+
+        let payout = if initiative.milestones.iter().all(|m| m.released) {
+            initiative.raised_amount - initiative.released_amount
+        } else {
+            milestone.amount
+        };
+        initiative.released_amount += payout;
+        env.storage().persistent().set(&DataKey::Initiative(initiative_id), &initiative);
+
+        token::Client::new(&env, &initiative.preferred_asset).transfer(&env.current_contract_address(), &initiative.recipient, &payout);
+
+        env.events().publish(
+            (symbol_short!("release"), initiative_id),
+            (milestone_index, payout)
+        );
+
+        Ok(payout)
+    }
+
+    // Claim a pro-rata share of the unreleased escrow once an initiative has missed its goal,
+    // either by passing its deadline or by being closed early.
+    // Anonymous contributions are claimed by passing the salt used when donating, which
+    // links the donor to the commitment on the ledger from then on.
+    pub fn claim_refund(env: Env, donor: Address, initiative_id: u32, anonymous_salt: Option<BytesN<32>>) -> Result<i128, DonationError> {
+        donor.require_auth();
+
+        let initiative = Self::get_initiative(env.clone(), initiative_id)?;
+        let ended = env.ledger().timestamp() > initiative.deadline || initiative.status == InitiativeStatus::Closed;
+        if !ended || initiative.raised_amount >= initiative.goal {
+            return Err(DonationError::RefundNotAvailable);
+        }
+
+        let donor_ref = match anonymous_salt {
+            Some(salt) => DonorRef::Anonymous(Self::donor_commitment(&env, &donor, &salt)),
+            None => DonorRef::Public(donor.clone()),
+        };
+        let contribution_key = DataKey::Contribution(initiative_id, donor_ref.clone());
+        let contributed = env.storage().persistent().get::<DataKey, i128>(&contribution_key).unwrap_or(0);
+        if contributed <= 0 {
+            return Err(DonationError::NothingToRefund);
+        }
+
+        // Releases need the goal to be met, so the escrow here is normally intact
+        let refund = contributed * (initiative.raised_amount - initiative.released_amount) / initiative.raised_amount;
+        env.storage().persistent().set(&contribution_key, &0i128);
+
+        token::Client::new(&env, &initiative.preferred_asset).transfer(&env.current_contract_address(), &donor, &refund);
+
+        env.events().publish(
+            (symbol_short!("refund"), initiative_id),
+            (donor_ref, refund)
+        );
+
+        Ok(refund)
+    }
+
+    // Get a donor's escrowed contribution to an initiative
+    pub fn get_contribution(env: Env, initiative_id: u32, donor: DonorRef) -> i128 {
+        env.storage().persistent().get::<DataKey, i128>(&DataKey::Contribution(initiative_id, donor)).unwrap_or(0)
+    }
+
+    // Get a donation receipt
+    pub fn get_receipt(env: Env, receipt_id: u64) -> Result<Receipt, DonationError> {
+        env.storage().persistent().get::<DataKey, Receipt>(&DataKey::Receipt(receipt_id)).ok_or(DonationError::ReceiptNotFound)
+    }
+
+    // Export an initiative's totals for consecutive reporting periods starting at
+    // from_timestamp, including empty periods, capped at MAX_REPORT_PERIODS
+    pub fn get_period_totals(env: Env, initiative_id: u32, from_timestamp: u64, periods: u64) -> Vec<PeriodTotal> {
+        let first = from_timestamp / REPORT_PERIOD;
         let mut result = Vec::new(&env);
-        
-        for (id, initiative) in contract.initiatives.iter() {
-            result.push_back((id, initiative));
+
+        for period in first..first.saturating_add(periods.min(MAX_REPORT_PERIODS)) {
+            let total = env.storage().persistent().get::<DataKey, PeriodTotal>(&DataKey::PeriodTotal(initiative_id, period)).unwrap_or(PeriodTotal {
+                period_start: period * REPORT_PERIOD,
+                amount: 0,
+                donation_count: 0,
+            });
+            result.push_back(total);
         }
-        
+
         result
     }
-    
-    // Get total donated to an initiative
-    pub fn get_total_donated(env: Env, initiative_id: u32) -> i128 {
-        let contract = Self::load_contract(&env);
-        contract.total_donated.get(initiative_id).unwrap_or(0)
-    }
-    
-    // Helper function to load contract state
-    fn load_contract(env: &Env) -> DonationContract {
-        let mut contract = DonationContract {
-            initiatives: Map::new(env),
-            donations: Map::new(env),
-            total_donated: Map::new(env),
-            next_initiative_id: 1,
+
+    // Register a swap adapter used to convert donations (admin only)
+    pub fn add_swap_adapter(env: Env, admin: Address, adapter: Address) -> Result<(), DonationError> {
+        Self::require_admin(&env, &admin)?;
+
+        let mut adapters = Self::list_swap_adapters(env.clone());
+        if adapters.contains(&adapter) {
+            return Err(DonationError::AdapterAlreadyRegistered);
+        }
+        adapters.push_back(adapter.clone());
+        env.storage().instance().set(&DataKey::SwapAdapters, &adapters);
+
+        env.events().publish(
+            (symbol_short!("adapter"), symbol_short!("add")),
+            adapter
+        );
+
+        Ok(())
+    }
+
+    // Unregister a swap adapter (admin only)
+    pub fn remove_swap_adapter(env: Env, admin: Address, adapter: Address) -> Result<(), DonationError> {
+        Self::require_admin(&env, &admin)?;
+
+        let mut adapters = Self::list_swap_adapters(env.clone());
+        let index = adapters.first_index_of(&adapter).ok_or(DonationError::AdapterNotFound)?;
+        adapters.remove(index);
+        env.storage().instance().set(&DataKey::SwapAdapters, &adapters);
+
+        env.events().publish(
+            (symbol_short!("adapter"), symbol_short!("remove")),
+            adapter
+        );
+
+        Ok(())
+    }
+
+    // Get registered swap adapters
+    pub fn list_swap_adapters(env: Env) -> Vec<Address> {
+        env.storage().instance().get::<DataKey, Vec<Address>>(&DataKey::SwapAdapters).unwrap_or(Vec::new(&env))
+    }
+
+    // Credit a donation to the initiative's escrow, issue its receipt and, for public donors,
+    // append it to their history. Returns the receipt ID.
+    fn record_donation(
+        env: &Env,
+        donor: DonorRef,
+        initiative_id: u32,
+        initiative: &mut Initiative,
+        from_asset: Address,
+        amount: i128,
+        converted_amount: i128,
+    ) -> u64 {
+        let timestamp = env.ledger().timestamp();
+        let receipt_id = env.storage().instance().get::<DataKey, u64>(&DataKey::NextReceiptId).unwrap_or(1);
+        env.storage().instance().set(&DataKey::NextReceiptId, &(receipt_id + 1));
+
+        let receipt = Receipt {
+            receipt_id,
+            initiative_id,
+            donor: donor.clone(),
+            amount: converted_amount,
+            asset: initiative.preferred_asset.clone(),
+            timestamp,
+        };
+        env.storage().persistent().set(&DataKey::Receipt(receipt_id), &receipt);
+
+        // Anonymous donations stay out of the per-address history
+        if let DonorRef::Public(address) = &donor {
+            let donation = Donation {
+                donor: donor.clone(),
+                receipt_id,
+                initiative_id,
+                from_asset,
+                to_asset: initiative.preferred_asset.clone(),
+                amount,
+                converted_amount,
+                timestamp,
+            };
+
+            let count = env.storage().persistent().get::<DataKey, u32>(&DataKey::DonationCount(address.clone())).unwrap_or(0);
+            let page_key = DataKey::DonationPage(address.clone(), count / DONATION_PAGE_SIZE);
+            let mut page = env.storage().persistent().get::<DataKey, Vec<Donation>>(&page_key).unwrap_or(Vec::new(env));
+            page.push_back(donation);
+            env.storage().persistent().set(&page_key, &page);
+            env.storage().persistent().set(&DataKey::DonationCount(address.clone()), &(count + 1));
+        }
+
+        // Roll the donation into its reporting period
+        let period = timestamp / REPORT_PERIOD;
+        let period_key = DataKey::PeriodTotal(initiative_id, period);
+        let mut period_total = env.storage().persistent().get::<DataKey, PeriodTotal>(&period_key).unwrap_or(PeriodTotal {
+            period_start: period * REPORT_PERIOD,
+            amount: 0,
+            donation_count: 0,
+        });
+        period_total.amount += converted_amount;
+        period_total.donation_count += 1;
+        env.storage().persistent().set(&period_key, &period_total);
+
+        // Track the donor's escrowed share for refunds
+        let contribution_key = DataKey::Contribution(initiative_id, donor.clone());
+        let contributed = env.storage().persistent().get::<DataKey, i128>(&contribution_key).unwrap_or(0);
+        env.storage().persistent().set(&contribution_key, &(contributed + converted_amount));
+
+        // Update initiative raised amount
+        initiative.raised_amount += converted_amount;
+        env.storage().persistent().set(&DataKey::Initiative(initiative_id), initiative);
+
+        env.events().publish(
+            (symbol_short!("donate"), initiative_id),
+            (donor, converted_amount, receipt_id)
+        );
+
+        receipt_id
+    }
+
+    // Commitment published in place of an anonymous donor's address
+    fn donor_commitment(env: &Env, donor: &Address, salt: &BytesN<32>) -> BytesN<32> {
+        let mut bytes_to_hash = donor.clone().to_xdr(env);
+        bytes_to_hash.extend_from_array(&salt.to_array());
+        env.crypto().sha256(&bytes_to_hash)
+    }
+
+    // Move an initiative between lifecycle states after checking the admin and the current state
+    fn transition(env: &Env, admin: &Address, initiative_id: u32, from: &[InitiativeStatus], to: InitiativeStatus) -> Result<(), DonationError> {
+        Self::require_admin(env, admin)?;
+
+        let mut initiative = Self::get_initiative(env.clone(), initiative_id)?;
+        let previous = initiative.status;
+        if !from.contains(&previous) {
+            return Err(DonationError::InvalidStatusTransition);
+        }
+
+        initiative.status = to;
+        env.storage().persistent().set(&DataKey::Initiative(initiative_id), &initiative);
+        Self::index_status(env, previous, initiative_id, false);
+        Self::index_status(env, to, initiative_id, true);
+
+        env.events().publish(
+            (symbol_short!("status"), initiative_id),
+            (previous, to)
+        );
+
+        Ok(())
+    }
+
+    // Add or remove an initiative from a status index
+    fn index_status(env: &Env, status: InitiativeStatus, initiative_id: u32, present: bool) {
+        if present {
+            Self::id_push(env, IdList::InitiativesByStatus(status), initiative_id);
+        } else {
+            Self::id_remove(env, IdList::InitiativesByStatus(status), initiative_id);
+        }
+    }
+
+    // Load a pledge and check the caller owns it
+    fn get_own_pledge(env: &Env, donor: &Address, pledge_id: u32) -> Result<Pledge, DonationError> {
+        let pledge = Self::get_pledge(env.clone(), pledge_id)?;
+        if pledge.donor != *donor {
+            return Err(DonationError::Unauthorized);
+        }
+
+        donor.require_auth();
+        Ok(pledge)
+    }
+
+    // Add or remove a pledge from the keeper's scan list
+    fn set_pledge_active(env: &Env, pledge_id: u32, active: bool) {
+        if active {
+            Self::id_push(env, IdList::ActivePledges, pledge_id);
+        } else {
+            Self::id_remove(env, IdList::ActivePledges, pledge_id);
+        }
+    }
+
+    fn id_count(env: &Env, list: &IdList) -> u32 {
+        env.storage().persistent().get::<DataKey, u32>(&DataKey::IdCount(list.clone())).unwrap_or(0)
+    }
+
+    // Get the ID at a position of a list
+    fn id_at(env: &Env, list: &IdList, pos: u32) -> Option<u32> {
+        env.storage().persistent().get::<DataKey, Vec<u32>>(&DataKey::IdPage(list.clone(), pos / ID_PAGE_SIZE))?
+            .get(pos % ID_PAGE_SIZE)
+    }
+
+    // IDs at positions `start..end` of a list, loading only the pages they span
+    fn id_range(env: &Env, list: &IdList, start: u32, end: u32) -> Vec<u32> {
+        let mut ids = Vec::new(env);
+        let mut i = start;
+        while i < end {
+            let page_number = i / ID_PAGE_SIZE;
+            let page_end = ((page_number + 1) * ID_PAGE_SIZE).min(end);
+            let page = env.storage().persistent().get::<DataKey, Vec<u32>>(&DataKey::IdPage(list.clone(), page_number)).unwrap_or(Vec::new(env));
+            for j in i..page_end {
+                if let Some(id) = page.get(j % ID_PAGE_SIZE) {
+                    ids.push_back(id);
+                }
+            }
+            i = page_end;
+        }
+        ids
+    }
+
+    // Append an ID to a list unless it is already there
+    fn id_push(env: &Env, list: IdList, id: u32) {
+        let position_key = DataKey::IdPosition(list.clone(), id);
+        if env.storage().persistent().has(&position_key) {
+            return;
+        }
+        let count = Self::id_count(env, &list);
+        let page_key = DataKey::IdPage(list.clone(), count / ID_PAGE_SIZE);
+        let mut page = env.storage().persistent().get::<DataKey, Vec<u32>>(&page_key).unwrap_or(Vec::new(env));
+        page.push_back(id);
+        env.storage().persistent().set(&page_key, &page);
+        env.storage().persistent().set(&position_key, &count);
+        env.storage().persistent().set(&DataKey::IdCount(list), &(count + 1));
+    }
+
+    // Remove an ID from a list by moving the last ID into its position
+    fn id_remove(env: &Env, list: IdList, id: u32) {
+        let position_key = DataKey::IdPosition(list.clone(), id);
+        let pos = match env.storage().persistent().get::<DataKey, u32>(&position_key) {
+            Some(pos) => pos,
+            None => return,
+        };
+        env.storage().persistent().remove(&position_key);
+
+        let last = Self::id_count(env, &list) - 1;
+        env.storage().persistent().set(&DataKey::IdCount(list.clone()), &last);
+
+        let last_page_key = DataKey::IdPage(list.clone(), last / ID_PAGE_SIZE);
+        let mut last_page = env.storage().persistent().get::<DataKey, Vec<u32>>(&last_page_key).unwrap_or(Vec::new(env));
+        let moved = match last_page.pop_back() {
+            Some(moved) => moved,
+            None => return,
         };
-        
-        // In a full implementation, we'd load from storage
-        contract
+        if last_page.is_empty() {
+            env.storage().persistent().remove(&last_page_key);
+        } else {
+            env.storage().persistent().set(&last_page_key, &last_page);
+        }
+        if pos == last {
+            return;
+        }
+
+        let page_key = DataKey::IdPage(list.clone(), pos / ID_PAGE_SIZE);
+        let mut page = env.storage().persistent().get::<DataKey, Vec<u32>>(&page_key).unwrap_or(Vec::new(env));
+        page.set(pos % ID_PAGE_SIZE, moved);
+        env.storage().persistent().set(&page_key, &page);
+        env.storage().persistent().set(&DataKey::IdPosition(list, moved), &pos);
+    }
+
+    // Pick the adapter quoting the most output; adapters that can't quote the pair are skipped
+    fn best_adapter(env: &Env, from_asset: &Address, to_asset: &Address, amount: i128) -> Result<Address, DonationError> {
+        let mut best: Option<(Address, i128)> = None;
+
+        for adapter in Self::list_swap_adapters(env.clone()).iter() {
+            if let Ok(Ok(quote)) = SwapAdapterClient::new(env, &adapter).try_quote(from_asset, to_asset, &amount) {
+                if quote > 0 && best.as_ref().map_or(true, |(_, best_quote)| quote > *best_quote) {
+                    best = Some((adapter, quote));
+                }
+            }
+        }
+
+        best.map(|(adapter, _)| adapter).ok_or(DonationError::NoSwapRoute)
+    }
+
+    // Check that the caller is the stored admin and has authorized the call
+    fn require_admin(env: &Env, admin: &Address) -> Result<(), DonationError> {
+        let stored_admin = Self::get_admin_internal(env)?;
+        if *admin != stored_admin {
+            return Err(DonationError::Unauthorized);
+        }
+
+        admin.require_auth();
+        Ok(())
     }
-    
-    // Helper function to save contract state
-    fn save_contract(env: &Env, contract: &DonationContract) {
-        // In a full implementation, we'd save to storage
+
+    // Get the contract admin
+    fn get_admin_internal(env: &Env) -> Result<Address, DonationError> {
+        match env.storage().instance().get::<DataKey, Address>(&DataKey::Admin) {
+            Some(admin) => Ok(admin),
+            None => Err(DonationError::NotInitialized),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Env};
-    
+    use soroban_sdk::{testutils::{Address as _, Events, Ledger}, Env, Symbol, TryFromVal};
+
+    fn activate(client: &DonationContractClient, admin: &Address, initiative_id: &u32) {
+        client.verify_initiative(admin, initiative_id);
+        client.activate_initiative(admin, initiative_id);
+    }
+
+    fn milestones(env: &Env, amounts: &[i128]) -> Vec<Milestone> {
+        let mut result = Vec::new(env);
+        for amount in amounts {
+            result.push_back(Milestone {
+                description: String::from_str(env, "Milestone"),
+                amount: *amount,
+                released: false,
+            });
+        }
+        result
+    }
+
     #[test]
     fn test_donation_flow() {
         let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DonationContract);
+        let client = DonationContractClient::new(&env, &contract_id);
+
         let admin = Address::generate(&env);
         let donor = Address::generate(&env);
         let recipient = Address::generate(&env);
-        
+
         // Initialize contract
-        let mut contract = DonationContract::initialize(env.clone(), admin.clone());
-        
+        client.initialize(&admin);
+
+        // Stellar asset contracts stand in for USDC and XLM
+        let usdc = env.register_stellar_asset_contract(admin.clone());
+        let xlm = env.register_stellar_asset_contract(admin.clone());
+        token::StellarAssetClient::new(&env, &usdc).mint(&donor, &1000);
+        token::StellarAssetClient::new(&env, &xlm).mint(&donor, &1000);
+
         // Create a health initiative
-        let preferred_asset = Asset {
-            code: String::from_str(&env, "USDC"),
-            issuer: Some(Address::generate(&env)),
-        };
-        
-        let initiative_id = DonationContract::create_initiative(
-            env.clone(),
-            admin,
-            String::from_str(&env, "Women's Health Research"),
-            String::from_str(&env, "Funding research for women's health issues"),
-            recipient,
-            preferred_asset.clone(),
-        );
-        
+        let name = String::from_str(&env, "Women's Health Research");
+        let description = String::from_str(&env, "Funding research for women's health issues");
+        let plan = milestones(&env, &[60, 40]);
+        let res = client.try_create_initiative(&donor, &name, &description, &recipient, &usdc, &100, &1000, &plan);
+        assert_eq!(res, Err(Ok(DonationError::Unauthorized)));
+        let res = client.try_create_initiative(&admin, &name, &description, &recipient, &usdc, &120, &1000, &plan);
+        assert_eq!(res, Err(Ok(DonationError::InvalidMilestones)));
+
+        let initiative_id = client.create_initiative(&admin, &name, &description, &recipient, &usdc, &100, &1000, &plan);
+
+        activate(&client, &admin, &initiative_id);
+        let second_id = client.create_initiative(&admin, &name, &description, &recipient, &usdc, &100, &1000, &plan);
+        activate(&client, &admin, &second_id);
+        assert_eq!((initiative_id, second_id), (1, 2));
+        assert_eq!(client.list_initiatives(&0, &10).len(), 2);
+
         // Donor makes a donation
-        let donation_asset = Asset {
-            code: String::from_str(&env, "XLM"),
-            issuer: None,
-        };
-        
         let amount = 100;
-        
-        DonationContract::donate_with_path_payment(
-            env.clone(),
-            donor.clone(),
-            initiative_id,
-            donation_asset,
-            amount,
-        ).unwrap();
-        
+
+        client.donate_with_path_payment(&donor, &initiative_id, &usdc, &amount, &amount, &None);
+        let res = client.try_donate_with_path_payment(&donor, &99, &usdc, &amount, &0, &None);
+        assert_eq!(res, Err(Ok(DonationError::InitiativeNotFound)));
+        let res = client.try_donate_with_path_payment(&donor, &initiative_id, &xlm, &amount, &0, &None);
+        assert_eq!(res, Err(Ok(DonationError::NoSwapRoute)));
+        let res = client.try_donate_with_path_payment(&donor, &initiative_id, &usdc, &0, &0, &None);
+        assert_eq!(res, Err(Ok(DonationError::InvalidAmount)));
+
+        // Tokens moved from the donor into escrow
+        let usdc_client = token::Client::new(&env, &usdc);
+        assert_eq!(usdc_client.balance(&donor), 900);
+        assert_eq!(usdc_client.balance(&contract_id), amount);
+        assert_eq!(usdc_client.balance(&recipient), 0);
+        assert_eq!(client.get_contribution(&initiative_id, &DonorRef::Public(donor.clone())), amount);
+
         // Check donation history
-        let history = DonationContract::get_donation_history(env.clone(), donor);
+        let history = client.get_donation_history(&donor, &0, &10);
         assert_eq!(history.len(), 1);
-        
+        let donation = history.get_unchecked(0);
+        assert_eq!((donation.from_asset, donation.to_asset, donation.converted_amount), (usdc.clone(), usdc.clone(), amount));
+
         // Check initiative raised amount
-        let initiative = DonationContract::get_initiative(env.clone(), initiative_id).unwrap();
+        let initiative = client.get_initiative(&initiative_id);
         assert_eq!(initiative.raised_amount, amount);
-        
+
         // Check total donated
-        let total = DonationContract::get_total_donated(env.clone(), initiative_id);
+        let total = client.get_total_donated(&initiative_id);
         assert_eq!(total, amount);
+
+        // Milestones are released by the admin or the oracle, never twice
+        let oracle = Address::generate(&env);
+        let res = client.try_release_milestone(&oracle, &initiative_id, &0);
+        assert_eq!(res, Err(Ok(DonationError::Unauthorized)));
+        client.set_oracle(&admin, &oracle);
+        assert_eq!(client.release_milestone(&oracle, &initiative_id, &0), 60);
+        let res = client.try_release_milestone(&admin, &initiative_id, &0);
+        assert_eq!(res, Err(Ok(DonationError::MilestoneAlreadyReleased)));
+        let res = client.try_release_milestone(&admin, &initiative_id, &2);
+        assert_eq!(res, Err(Ok(DonationError::MilestoneNotFound)));
+        assert_eq!(client.release_milestone(&admin, &initiative_id, &1), 40);
+        assert_eq!(usdc_client.balance(&recipient), amount);
+        assert_eq!(usdc_client.balance(&contract_id), 0);
+
+        // The second initiative never got funded
+        let res = client.try_release_milestone(&admin, &second_id, &0);
+        assert_eq!(res, Err(Ok(DonationError::GoalNotReached)));
+
+        // A funded initiative offers no refunds
+        env.ledger().with_mut(|li| li.timestamp = 1001);
+        let res = client.try_claim_refund(&donor, &initiative_id, &None);
+        assert_eq!(res, Err(Ok(DonationError::RefundNotAvailable)));
+        let res = client.try_donate_with_path_payment(&donor, &second_id, &usdc, &amount, &0, &None);
+        assert_eq!(res, Err(Ok(DonationError::DeadlinePassed)));
+    }
+
+    #[test]
+    fn test_refund_when_goal_missed() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DonationContract);
+        let client = DonationContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        client.initialize(&admin);
+
+        let usdc = env.register_stellar_asset_contract(admin.clone());
+        token::StellarAssetClient::new(&env, &usdc).mint(&alice, &100);
+        token::StellarAssetClient::new(&env, &usdc).mint(&bob, &100);
+
+        let name = String::from_str(&env, "Mobile Clinic");
+        let initiative_id = client.create_initiative(&admin, &name, &name, &recipient, &usdc, &500, &1000, &milestones(&env, &[500]));
+        activate(&client, &admin, &initiative_id);
+
+        let salt = BytesN::from_array(&env, &[7; 32]);
+        client.donate_with_path_payment(&alice, &initiative_id, &usdc, &30, &30, &Some(salt.clone()));
+        client.donate_with_path_payment(&bob, &initiative_id, &usdc, &70, &70, &None);
+
+        // Refunds only open once the deadline passes
+        let res = client.try_claim_refund(&alice, &initiative_id, &None);
+        assert_eq!(res, Err(Ok(DonationError::RefundNotAvailable)));
+
+        env.ledger().with_mut(|li| li.timestamp = 1001);
+        let res = client.try_claim_refund(&alice, &initiative_id, &None);
+        assert_eq!(res, Err(Ok(DonationError::NothingToRefund)));
+        assert_eq!(client.claim_refund(&alice, &initiative_id, &Some(salt)), 30);
+        assert_eq!(client.claim_refund(&bob, &initiative_id, &None), 70);
+        let res = client.try_claim_refund(&bob, &initiative_id, &None);
+        assert_eq!(res, Err(Ok(DonationError::NothingToRefund)));
+
+        let usdc_client = token::Client::new(&env, &usdc);
+        assert_eq!((usdc_client.balance(&alice), usdc_client.balance(&bob)), (100, 100));
+        assert_eq!(usdc_client.balance(&contract_id), 0);
+    }
+
+    #[test]
+    fn test_donation_through_swap_adapter() {
+        use mock_swap_adapter::{MockSwapAdapter, MockSwapAdapterClient, RATE_SCALE};
+
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DonationContract);
+        let client = DonationContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let donor = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        client.initialize(&admin);
+
+        let usdc = env.register_stellar_asset_contract(admin.clone());
+        let xlm = env.register_stellar_asset_contract(admin.clone());
+        token::StellarAssetClient::new(&env, &xlm).mint(&donor, &1000);
+
+        // Two AMMs with different XLM -> USDC rates, both holding USDC liquidity
+        let cheap_amm = env.register_contract(None, MockSwapAdapter);
+        let good_amm = env.register_contract(None, MockSwapAdapter);
+        for (amm, rate) in [(&cheap_amm, RATE_SCALE / 20), (&good_amm, RATE_SCALE / 10)] {
+            let amm_client = MockSwapAdapterClient::new(&env, amm);
+            amm_client.initialize(&admin);
+            amm_client.set_rate(&xlm, &usdc, &rate);
+            token::StellarAssetClient::new(&env, &usdc).mint(amm, &1000);
+        }
+
+        let name = String::from_str(&env, "Clinic Supplies");
+        let initiative_id = client.create_initiative(&admin, &name, &name, &recipient, &usdc, &25, &1000, &milestones(&env, &[25]));
+        activate(&client, &admin, &initiative_id);
+
+        let res = client.try_add_swap_adapter(&donor, &good_amm);
+        assert_eq!(res, Err(Ok(DonationError::Unauthorized)));
+        client.add_swap_adapter(&admin, &cheap_amm);
+        client.add_swap_adapter(&admin, &good_amm);
+        let res = client.try_add_swap_adapter(&admin, &good_amm);
+        assert_eq!(res, Err(Ok(DonationError::AdapterAlreadyRegistered)));
+        assert_eq!(client.list_swap_adapters().len(), 2);
+
+        // The better quote wins: 200 XLM -> 20 USDC
+        client.donate_with_path_payment(&donor, &initiative_id, &xlm, &200, &20, &None);
+        let usdc_client = token::Client::new(&env, &usdc);
+        assert_eq!(usdc_client.balance(&contract_id), 20);
+        assert_eq!(token::Client::new(&env, &xlm).balance(&good_amm), 200);
+        assert_eq!(client.get_total_donated(&initiative_id), 20);
+
+        let donation = client.get_donation_history(&donor, &0, &10).get_unchecked(0);
+        assert_eq!((donation.from_asset, donation.to_asset), (xlm.clone(), usdc.clone()));
+        assert_eq!((donation.amount, donation.converted_amount), (200, 20));
+
+        // Once the good AMM is gone only the cheaper rate is available
+        client.remove_swap_adapter(&admin, &good_amm);
+        let res = client.try_remove_swap_adapter(&admin, &good_amm);
+        assert_eq!(res, Err(Ok(DonationError::AdapterNotFound)));
+        client.donate_with_path_payment(&donor, &initiative_id, &xlm, &100, &5, &None);
+        assert_eq!(usdc_client.balance(&contract_id), 25);
+        assert_eq!(client.get_total_donated(&initiative_id), 25);
+    }
+
+    #[test]
+    fn test_recurring_pledges() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DonationContract);
+        let client = DonationContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let donor = Address::generate(&env);
+        let other = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        client.initialize(&admin);
+
+        let usdc = env.register_stellar_asset_contract(admin.clone());
+        token::StellarAssetClient::new(&env, &usdc).mint(&donor, &100);
+        let usdc_client = token::Client::new(&env, &usdc);
+
+        let name = String::from_str(&env, "Cycle Care Kits");
+        let initiative_id = client.create_initiative(&admin, &name, &name, &recipient, &usdc, &500, &10_000, &milestones(&env, &[500]));
+        activate(&client, &admin, &initiative_id);
+
+        let res = client.try_create_pledge(&donor, &initiative_id, &10, &0, &3);
+        assert_eq!(res, Err(Ok(DonationError::InvalidPledge)));
+        let pledge_id = client.create_pledge(&donor, &initiative_id, &10, &100, &3);
+        assert_eq!(client.list_pledges(&donor).len(), 1);
+
+        // Nothing moves until the donor grants an allowance
+        assert_eq!(client.process_due_pledges(&10), 0);
+        usdc_client.approve(&donor, &contract_id, &30, &1000);
+        assert_eq!(client.process_due_pledges(&10), 1);
+        assert_eq!(client.process_due_pledges(&10), 0);
+        assert_eq!(usdc_client.balance(&contract_id), 10);
+
+        // Paused pledges are not charged and resume without back-charging
+        let res = client.try_pause_pledge(&other, &pledge_id);
+        assert_eq!(res, Err(Ok(DonationError::Unauthorized)));
+        client.pause_pledge(&donor, &pledge_id);
+        env.ledger().with_mut(|li| li.timestamp = 300);
+        assert_eq!(client.process_due_pledges(&10), 0);
+        client.resume_pledge(&donor, &pledge_id);
+        assert_eq!(client.process_due_pledges(&10), 1);
+        assert_eq!(client.get_pledge(&pledge_id).next_due, 400);
+
+        env.ledger().with_mut(|li| li.timestamp = 400);
+        assert_eq!(client.process_due_pledges(&10), 1);
+        let pledge = client.get_pledge(&pledge_id);
+        assert_eq!((pledge.status, pledge.executed_count), (PledgeStatus::Completed, 3));
+        let res = client.try_cancel_pledge(&donor, &pledge_id);
+        assert_eq!(res, Err(Ok(DonationError::InvalidPledgeState)));
+
+        // Every execution lands in the donation history and escrow
+        assert_eq!(client.get_donation_history(&donor, &0, &10).len(), 3);
+        assert_eq!(client.get_donation_history(&donor, &1, &10).len(), 2);
+        assert_eq!(client.get_donation_count(&donor), 3);
+        assert_eq!(client.get_contribution(&initiative_id, &DonorRef::Public(donor.clone())), 30);
+        assert_eq!(usdc_client.balance(&donor), 70);
+
+        // Cancelled pledges stop executing
+        let second = client.create_pledge(&donor, &initiative_id, &10, &100, &5);
+        client.cancel_pledge(&donor, &second);
+        usdc_client.approve(&donor, &contract_id, &50, &1000);
+        assert_eq!(client.process_due_pledges(&10), 0);
+        assert_eq!(client.get_pledge(&second).status, PledgeStatus::Cancelled);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_pledge_runs_resume_and_skip_failures() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DonationContract);
+        let client = DonationContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        client.initialize(&admin);
+
+        let usdc = env.register_stellar_asset_contract(admin.clone());
+        let usdc_client = token::Client::new(&env, &usdc);
+        let name = String::from_str(&env, "Pad Drive");
+        let initiative_id = client.create_initiative(&admin, &name, &name, &recipient, &usdc, &500, &10_000, &milestones(&env, &[500]));
+        activate(&client, &admin, &initiative_id);
+
+        // The second donor never grants an allowance
+        let donors = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+        for (i, donor) in donors.iter().enumerate() {
+            token::StellarAssetClient::new(&env, &usdc).mint(donor, &100);
+            client.create_pledge(donor, &initiative_id, &10, &100, &2);
+            if i != 1 {
+                usdc_client.approve(donor, &contract_id, &20, &1000);
+            }
+        }
+
+        // `limit` bounds the pledges scanned, and the failing transfer does not revert the run
+        assert_eq!(client.process_due_pledges(&2), 1);
+        assert_eq!(usdc_client.balance(&contract_id), 10);
+        assert_eq!(client.process_due_pledges(&2), 1);
+        assert_eq!(usdc_client.balance(&contract_id), 20);
+        assert_eq!(client.get_donation_count(&donors[1]), 0);
+        assert_eq!(client.process_due_pledges(&2), 0);
+
+        // Completed pledges leave the scan list within the run that completes them
+        env.ledger().with_mut(|li| li.timestamp += 100);
+        assert_eq!(client.process_due_pledges(&3), 2);
+        assert_eq!(client.get_pledge(&1).status, PledgeStatus::Completed);
+        assert_eq!(client.process_due_pledges(&3), 0);
+        assert_eq!(usdc_client.balance(&contract_id), 40);
+    }
+
+    #[test]
+    fn test_receipts_and_period_totals() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DonationContract);
+        let client = DonationContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let donor = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        client.initialize(&admin);
+
+        let usdc = env.register_stellar_asset_contract(admin.clone());
+        token::StellarAssetClient::new(&env, &usdc).mint(&donor, &1000);
+
+        let name = String::from_str(&env, "Menstrual Health Education");
+        let initiative_id = client.create_initiative(&admin, &name, &name, &recipient, &usdc, &500, &(REPORT_PERIOD * 3), &milestones(&env, &[500]));
+        activate(&client, &admin, &initiative_id);
+
+        // A public donation in the first period
+        env.ledger().with_mut(|li| li.timestamp = 10);
+        let public_receipt = client.donate_with_path_payment(&donor, &initiative_id, &usdc, &40, &40, &None);
+        let receipt = client.get_receipt(&public_receipt);
+        assert_eq!(receipt, Receipt {
+            receipt_id: public_receipt,
+            initiative_id,
+            donor: DonorRef::Public(donor.clone()),
+            amount: 40,
+            asset: usdc.clone(),
+            timestamp: 10,
+        });
+
+        // An anonymous donation in the second period only exposes the commitment
+        env.ledger().with_mut(|li| li.timestamp = REPORT_PERIOD + 5);
+        let salt = BytesN::from_array(&env, &[1; 32]);
+        let anonymous_receipt = client.donate_with_path_payment(&donor, &initiative_id, &usdc, &60, &60, &Some(salt.clone()));
+        let receipt = client.get_receipt(&anonymous_receipt);
+        let mut preimage = donor.clone().to_xdr(&env);
+        preimage.extend_from_array(&salt.to_array());
+        assert_eq!(receipt.donor, DonorRef::Anonymous(env.crypto().sha256(&preimage)));
+        assert_eq!(client.get_contribution(&initiative_id, &receipt.donor), 60);
+
+        let history = client.get_donation_history(&donor, &0, &10);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.get_unchecked(0).receipt_id, public_receipt);
+        assert_eq!(client.try_get_receipt(&99), Err(Ok(DonationError::ReceiptNotFound)));
+
+        // Export covers both periods plus an empty third one
+        let totals = client.get_period_totals(&initiative_id, &0, &3);
+        assert_eq!(totals, soroban_sdk::vec![
+            &env,
+            PeriodTotal { period_start: 0, amount: 40, donation_count: 1 },
+            PeriodTotal { period_start: REPORT_PERIOD, amount: 60, donation_count: 1 },
+            PeriodTotal { period_start: REPORT_PERIOD * 2, amount: 0, donation_count: 0 },
+        ]);
+    }
+
+    #[test]
+    fn test_initiative_lifecycle() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, DonationContract);
+        let client = DonationContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let donor = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let new_recipient = Address::generate(&env);
+        client.initialize(&admin);
+
+        let usdc = env.register_stellar_asset_contract(admin.clone());
+        token::StellarAssetClient::new(&env, &usdc).mint(&donor, &1000);
+
+        let name = String::from_str(&env, "Period Poverty Relief");
+        let first = client.create_initiative(&admin, &name, &name, &recipient, &usdc, &100, &1_000_000, &milestones(&env, &[100]));
+        let second = client.create_initiative(&admin, &name, &name, &recipient, &usdc, &100, &1_000_000, &milestones(&env, &[100]));
+        assert_eq!(client.get_initiative(&first).status, InitiativeStatus::Draft);
+        assert_eq!(client.list_initiatives_by_status(&InitiativeStatus::Draft, &0, &10).len(), 2);
+
+        // Drafts accept no funds and cannot skip verification
+        let res = client.try_donate_with_path_payment(&donor, &first, &usdc, &10, &10, &None);
+        assert_eq!(res, Err(Ok(DonationError::InitiativeNotActive)));
+        let res = client.try_activate_initiative(&admin, &first);
+        assert_eq!(res, Err(Ok(DonationError::InvalidStatusTransition)));
+        let res = client.try_verify_initiative(&donor, &first);
+        assert_eq!(res, Err(Ok(DonationError::Unauthorized)));
+
+        activate(&client, &admin, &first);
+        client.donate_with_path_payment(&donor, &first, &usdc, &10, &10, &None);
+
+        // Every transition is announced
+        let status_events = env.events().all().iter().filter(|(_, topics, _)| {
+            Symbol::try_from_val(&env, &topics.get_unchecked(0)) == Ok(symbol_short!("status"))
+        }).count();
+        assert_eq!(status_events, 2);
+
+        // Paused initiatives stop accepting donations
+        client.pause_initiative(&admin, &first);
+        let res = client.try_donate_with_path_payment(&donor, &first, &usdc, &10, &10, &None);
+        assert_eq!(res, Err(Ok(DonationError::InitiativeNotActive)));
+        client.activate_initiative(&admin, &first);
+
+        // Status listings are paginated
+        let active = client.list_initiatives_by_status(&InitiativeStatus::Active, &0, &10);
+        assert_eq!(active.len(), 1);
+        assert_eq!(active.get_unchecked(0).0, first);
+        let drafts = client.list_initiatives_by_status(&InitiativeStatus::Draft, &0, &10);
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts.get_unchecked(0).0, second);
+        assert_eq!(client.list_initiatives_by_status(&InitiativeStatus::Draft, &1, &10).len(), 0);
+        assert_eq!(client.list_initiatives(&1, &10).len(), 1);
+
+        // Metadata can be edited until the initiative closes
+        let renamed = String::from_str(&env, "Period Poverty Relief 2026");
+        client.update_initiative(&admin, &second, &renamed, &renamed);
+        assert_eq!(client.get_initiative(&second).name, renamed);
+
+        // Recipient changes wait out the timelock
+        let effective_at = client.propose_recipient_change(&admin, &first, &new_recipient);
+        let res = client.try_apply_recipient_change(&first);
+        assert_eq!(res, Err(Ok(DonationError::TimelockNotExpired)));
+        env.ledger().with_mut(|li| li.timestamp = effective_at);
+        client.apply_recipient_change(&first);
+        assert_eq!(client.get_initiative(&first).recipient, new_recipient);
+        let res = client.try_get_pending_recipient(&first);
+        assert_eq!(res, Err(Ok(DonationError::NoPendingRecipient)));
+
+        client.propose_recipient_change(&admin, &first, &recipient);
+        client.cancel_recipient_change(&admin, &first);
+        assert_eq!(client.try_apply_recipient_change(&first), Err(Ok(DonationError::NoPendingRecipient)));
+
+        // Closing before the goal is met opens refunds immediately
+        client.close_initiative(&admin, &first);
+        let res = client.try_close_initiative(&admin, &first);
+        assert_eq!(res, Err(Ok(DonationError::InvalidStatusTransition)));
+        let res = client.try_update_initiative(&admin, &first, &renamed, &renamed);
+        assert_eq!(res, Err(Ok(DonationError::InvalidStatusTransition)));
+        assert_eq!(client.claim_refund(&donor, &first, &None), 10);
+        assert_eq!(client.list_initiatives_by_status(&InitiativeStatus::Closed, &0, &10).len(), 1);
+    }
+}
@@ -0,0 +1,136 @@
+#![no_std]
+use soroban_sdk::{contract, contracterror, contractimpl, contracttype, symbol_short, token, Address, Env};
+
+// Fixed-rate AMM stand-in implementing the donation contract's SwapAdapter interface,
+// so multi-asset donations can be exercised offline.
+
+#[contracterror]
+#[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum AdapterError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    PairNotSupported = 3,
+    InvalidAmount = 4,
+    SlippageExceeded = 5,
+    InsufficientLiquidity = 6,
+}
+
+// Rates are expressed in units of `to_asset` per RATE_SCALE units of `from_asset`
+pub const RATE_SCALE: i128 = 10_000_000;
+
+// Storage keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Admin,                  // Address allowed to set rates
+    Rate(Address, Address), // (from_asset, to_asset) -> rate
+}
+
+#[contract]
+pub struct MockSwapAdapter;
+
+#[contractimpl]
+impl MockSwapAdapter {
+    // Initialize the adapter
+    pub fn initialize(env: Env, admin: Address) -> Result<(), AdapterError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(AdapterError::AlreadyInitialized);
+        }
+
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        Ok(())
+    }
+
+    // Set the fixed conversion rate for a pair (admin only)
+    pub fn set_rate(env: Env, from_asset: Address, to_asset: Address, rate: i128) -> Result<(), AdapterError> {
+        let admin = env.storage().instance().get::<DataKey, Address>(&DataKey::Admin).ok_or(AdapterError::NotInitialized)?;
+        admin.require_auth();
+
+        if rate <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+
+        env.storage().instance().set(&DataKey::Rate(from_asset, to_asset), &rate);
+        Ok(())
+    }
+
+    // Amount of `to_asset` a swap of `amount_in` would currently produce
+    pub fn quote(env: Env, from_asset: Address, to_asset: Address, amount_in: i128) -> Result<i128, AdapterError> {
+        if amount_in <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+
+        let rate = env.storage().instance().get::<DataKey, i128>(&DataKey::Rate(from_asset, to_asset)).ok_or(AdapterError::PairNotSupported)?;
+        Ok(amount_in * rate / RATE_SCALE)
+    }
+
+    // Pull `amount_in` from `payer`, pay out at least `min_out` of `to_asset` to `to`
+    pub fn swap(
+        env: Env,
+        payer: Address,
+        from_asset: Address,
+        to_asset: Address,
+        amount_in: i128,
+        min_out: i128,
+        to: Address,
+    ) -> Result<i128, AdapterError> {
+        payer.require_auth();
+
+        let amount_out = Self::quote(env.clone(), from_asset.clone(), to_asset.clone(), amount_in)?;
+        if amount_out < min_out {
+            return Err(AdapterError::SlippageExceeded);
+        }
+
+        let reserve = token::Client::new(&env, &to_asset);
+        if reserve.balance(&env.current_contract_address()) < amount_out {
+            return Err(AdapterError::InsufficientLiquidity);
+        }
+
+        token::Client::new(&env, &from_asset).transfer(&payer, &env.current_contract_address(), &amount_in);
+        reserve.transfer(&env.current_contract_address(), &to, &amount_out);
+
+        env.events().publish(
+            (symbol_short!("swap"), from_asset, to_asset),
+            (amount_in, amount_out)
+        );
+
+        Ok(amount_out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_quote_and_swap() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, MockSwapAdapter);
+        let client = MockSwapAdapterClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let trader = Address::generate(&env);
+        let xlm = env.register_stellar_asset_contract(admin.clone());
+        let usdc = env.register_stellar_asset_contract(admin.clone());
+        token::StellarAssetClient::new(&env, &xlm).mint(&trader, &1000);
+        token::StellarAssetClient::new(&env, &usdc).mint(&contract_id, &50);
+
+        client.initialize(&admin);
+        assert_eq!(client.try_quote(&xlm, &usdc, &100), Err(Ok(AdapterError::PairNotSupported)));
+
+        // 1 XLM = 0.1 USDC
+        client.set_rate(&xlm, &usdc, &(RATE_SCALE / 10));
+        assert_eq!(client.quote(&xlm, &usdc, &100), 10);
+
+        assert_eq!(client.try_swap(&trader, &xlm, &usdc, &100, &11, &trader), Err(Ok(AdapterError::SlippageExceeded)));
+        assert_eq!(client.try_swap(&trader, &xlm, &usdc, &1000, &0, &trader), Err(Ok(AdapterError::InsufficientLiquidity)));
+        assert_eq!(client.swap(&trader, &xlm, &usdc, &100, &10, &trader), 10);
+
+        assert_eq!(token::Client::new(&env, &xlm).balance(&trader), 900);
+        assert_eq!(token::Client::new(&env, &usdc).balance(&trader), 10);
+    }
+}
@@ -0,0 +1,374 @@
+#![no_std]
+use soroban_sdk::{contract, contractclient, contractimpl, contracttype, Address, BytesN, Env, Symbol, Vec, Bytes, symbol_short, contracterror};
+use soroban_sdk::auth::{Context, CustomAccountInterface};
+use soroban_sdk::xdr::ToXdr;
+
+#[contracterror]
+#[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum AccountError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    PasskeyNotFound = 3,
+    SessionRejected = 4,
+    AddressMismatch = 5,
+}
+
+/// Storage keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    AuthContract,
+}
+
+/// Mirror of `AuthContract`'s passkey record, decoded from cross-contract calls
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Passkey {
+    pub user: Address,
+    pub pubkey: BytesN<32>,
+    pub metadata: Bytes,
+}
+
+/// Signature accepted by `__check_auth`
+#[derive(Clone)]
+#[contracttype]
+pub enum AccountSignature {
+    Passkey(BytesN<64>), // ed25519 signature over the auth payload by the registered passkey
+    Session(BytesN<32>, BytesN<64>), // Session pubkey and its ed25519 signature over the payload
+}
+
+/// The subset of `AuthContract` used by the account
+#[contractclient(name = "AuthClient")]
+pub trait AuthInterface {
+    fn register_passkey(env: Env, user: Address, pubkey: BytesN<32>, metadata: Bytes);
+    fn get_passkey(env: Env, user: Address) -> Passkey;
+    fn consume_session(env: Env, user: Address, session_pubkey: BytesN<32>, contract: Address, fn_name: Symbol);
+}
+
+/// Smart wallet whose address is a CycleBuddy user. Any `require_auth` on this
+/// address is satisfied by a signature from the passkey that `AuthContract`
+/// holds for it, so passkey rotation and recovery there apply here as well.
+/// Session keys issued through `AuthContract` can stand in for the passkey
+/// within their scope.
+#[contract]
+pub struct PasskeyAccount;
+
+#[contractimpl]
+impl PasskeyAccount {
+    /// Bind the account to an auth contract and register its first passkey there.
+    /// The account must have been deployed by `deployer` with `deployment_salt` over
+    /// the same arguments, so nobody who sees the deployment can initialize it
+    /// with a passkey or auth contract of their own.
+    pub fn initialize(
+        env: Env,
+        deployer: Address,
+        auth_contract: Address,
+        pubkey: BytesN<32>,
+        metadata: Bytes,
+    ) -> Result<(), AccountError> {
+        if env.storage().instance().has(&DataKey::AuthContract) {
+            return Err(AccountError::AlreadyInitialized);
+        }
+        let salt = Self::deployment_salt(env.clone(), auth_contract.clone(), pubkey.clone(), metadata.clone());
+        if env.deployer().with_address(deployer, salt).deployed_address() != env.current_contract_address() {
+            return Err(AccountError::AddressMismatch);
+        }
+        env.storage().instance().set(&DataKey::AuthContract, &auth_contract);
+
+        // The account is the direct invoker here, so its own `require_auth` passes
+        let account = env.current_contract_address();
+        AuthClient::new(&env, &auth_contract).register_passkey(&account, &pubkey, &metadata);
+
+        env.events().publish(
+            (symbol_short!("init"), symbol_short!("account")),
+            (account, auth_contract)
+        );
+
+        Ok(())
+    }
+
+    /// Salt to deploy an account with, committing its address to the arguments
+    /// `initialize` will be called with
+    pub fn deployment_salt(env: Env, auth_contract: Address, pubkey: BytesN<32>, metadata: Bytes) -> BytesN<32> {
+        env.crypto().sha256(&(auth_contract, pubkey, metadata).to_xdr(&env))
+    }
+
+    /// Get the auth contract holding this account's passkey
+    pub fn get_auth_contract(env: Env) -> Result<Address, AccountError> {
+        Self::get_auth_contract_internal(&env)
+    }
+
+    fn get_auth_contract_internal(env: &Env) -> Result<Address, AccountError> {
+        match env.storage().instance().get::<DataKey, Address>(&DataKey::AuthContract) {
+            Some(addr) => Ok(addr),
+            None => Err(AccountError::NotInitialized),
+        }
+    }
+
+    fn get_passkey_internal(env: &Env) -> Result<Passkey, AccountError> {
+        let auth_contract = Self::get_auth_contract_internal(env)?;
+        match AuthClient::new(env, &auth_contract).try_get_passkey(&env.current_contract_address()) {
+            Ok(Ok(passkey)) => Ok(passkey),
+            _ => Err(AccountError::PasskeyNotFound),
+        }
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for PasskeyAccount {
+    type Signature = AccountSignature;
+    type Error = AccountError;
+
+    /// Verify that the payload was signed by the account's current passkey, or
+    /// by a session key whose scope covers every call being authorized
+    #[allow(non_snake_case)]
+    fn __check_auth(
+        env: Env,
+        signature_payload: BytesN<32>,
+        signature: AccountSignature,
+        auth_contexts: Vec<Context>,
+    ) -> Result<(), AccountError> {
+        match signature {
+            AccountSignature::Passkey(sig) => {
+                let passkey = Self::get_passkey_internal(&env)?;
+                // Traps on an invalid signature, which fails the authorization
+                env.crypto().ed25519_verify(&passkey.pubkey, &signature_payload.into(), &sig);
+            }
+            AccountSignature::Session(session_pubkey, sig) => {
+                env.crypto().ed25519_verify(&session_pubkey, &signature_payload.into(), &sig);
+
+                let auth = AuthClient::new(&env, &Self::get_auth_contract_internal(&env)?);
+                let account = env.current_contract_address();
+                for context in auth_contexts.iter() {
+                    let call = match context {
+                        Context::Contract(call) => call,
+                        // Sessions never authorize contract deployment
+                        Context::CreateContractHostFn(_) => return Err(AccountError::SessionRejected),
+                    };
+                    if auth.try_consume_session(&account, &session_pubkey, &call.contract, &call.fn_name).is_err() {
+                        return Err(AccountError::SessionRejected);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Unit tests for the passkey account
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use cyclebuddy_auth_contract::{AuthContract, AuthContractClient, SessionScope};
+    use soroban_sdk::auth::ContractContext;
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{vec, IntoVal};
+
+    fn setup(env: &Env, signing_key: &SigningKey) -> (Address, AuthContractClient<'static>) {
+        env.mock_all_auths();
+        let auth_id = env.register_contract(None, AuthContract);
+        let auth_client = AuthContractClient::new(env, &auth_id);
+        auth_client.initialize(&Address::generate(env));
+
+        let deployer = Address::generate(env);
+        let pubkey = BytesN::from_array(env, &signing_key.verifying_key().to_bytes());
+        let account_id = deploy_account(env, &deployer, &auth_id, &pubkey);
+        PasskeyAccountClient::new(env, &account_id).initialize(&deployer, &auth_id, &pubkey, &Bytes::new(env));
+
+        (account_id, auth_client)
+    }
+
+    // Register the account where `deployer` would deploy it for these arguments
+    fn deploy_account(env: &Env, deployer: &Address, auth_id: &Address, pubkey: &BytesN<32>) -> Address {
+        let salt = PasskeyAccount::deployment_salt(env.clone(), auth_id.clone(), pubkey.clone(), Bytes::new(env));
+        let account_id = env.deployer().with_address(deployer.clone(), salt).deployed_address();
+        env.register_contract(Some(&account_id), PasskeyAccount)
+    }
+
+    fn sign(env: &Env, signing_key: &SigningKey, payload: &BytesN<32>) -> AccountSignature {
+        let sig = signing_key.sign(&payload.to_array()).to_bytes();
+        AccountSignature::Passkey(BytesN::from_array(env, &sig))
+    }
+
+    #[test]
+    fn test_initialize_registers_passkey() {
+        let env = Env::default();
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (account_id, auth_client) = setup(&env, &signing_key);
+
+        let passkey = auth_client.get_passkey(&account_id);
+        assert_eq!(passkey.pubkey.to_array(), signing_key.verifying_key().to_bytes());
+
+        let client = PasskeyAccountClient::new(&env, &account_id);
+        assert_eq!(client.get_auth_contract(), auth_client.address);
+        let res = client.try_initialize(&Address::generate(&env), &auth_client.address, &passkey.pubkey, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(AccountError::AlreadyInitialized)));
+    }
+
+    #[test]
+    fn test_initialize_rejects_front_running() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let auth_id = env.register_contract(None, AuthContract);
+        AuthContractClient::new(&env, &auth_id).initialize(&Address::generate(&env));
+
+        let deployer = Address::generate(&env);
+        let pubkey = BytesN::from_array(&env, &SigningKey::from_bytes(&[7; 32]).verifying_key().to_bytes());
+        let account_id = deploy_account(&env, &deployer, &auth_id, &pubkey);
+        let client = PasskeyAccountClient::new(&env, &account_id);
+
+        // Someone who sees the deployment can't swap in their own passkey or auth contract
+        let attacker_pk = BytesN::from_array(&env, &SigningKey::from_bytes(&[8; 32]).verifying_key().to_bytes());
+        let res = client.try_initialize(&deployer, &auth_id, &attacker_pk, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(AccountError::AddressMismatch)));
+        let fake_auth = env.register_contract(None, AuthContract);
+        let res = client.try_initialize(&deployer, &fake_auth, &pubkey, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(AccountError::AddressMismatch)));
+        let res = client.try_initialize(&Address::generate(&env), &auth_id, &pubkey, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(AccountError::AddressMismatch)));
+
+        client.initialize(&deployer, &auth_id, &pubkey, &Bytes::new(&env));
+        assert_eq!(client.get_auth_contract(), auth_id);
+    }
+
+    #[test]
+    fn test_check_auth_accepts_passkey_signature() {
+        let env = Env::default();
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (account_id, _) = setup(&env, &signing_key);
+
+        let payload = BytesN::from_array(&env, &[1; 32]);
+        let res = env.try_invoke_contract_check_auth::<AccountError>(
+            &account_id,
+            &payload,
+            sign(&env, &signing_key, &payload).into_val(&env),
+            &vec![&env],
+        );
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn test_check_auth_requires_initialization() {
+        let env = Env::default();
+        let account_id = env.register_contract(None, PasskeyAccount);
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+
+        let payload = BytesN::from_array(&env, &[1; 32]);
+        let res = env.try_invoke_contract_check_auth::<AccountError>(
+            &account_id,
+            &payload,
+            sign(&env, &signing_key, &payload).into_val(&env),
+            &vec![&env],
+        );
+        assert_eq!(res, Err(Ok(AccountError::NotInitialized)));
+    }
+
+    #[test]
+    fn test_check_auth_follows_recovered_passkey() {
+        let env = Env::default();
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (account_id, auth_client) = setup(&env, &signing_key);
+
+        let recovery_key = SigningKey::from_bytes(&[9; 32]);
+        let recovery_pk = BytesN::from_array(&env, &recovery_key.verifying_key().to_bytes());
+        auth_client.add_recovery_key(&account_id, &recovery_pk);
+        let new_key = SigningKey::from_bytes(&[10; 32]);
+        let new_pk = BytesN::from_array(&env, &new_key.verifying_key().to_bytes());
+        let message: std::vec::Vec<u8> = auth_client.recovery_message(&account_id, &new_pk).iter().collect();
+        let recovery_sig = BytesN::from_array(&env, &recovery_key.sign(&message).to_bytes());
+        auth_client.recover_account(&account_id, &recovery_pk, &new_pk, &Bytes::new(&env), &recovery_sig);
+
+        let payload = BytesN::from_array(&env, &[2; 32]);
+        let new_res = env.try_invoke_contract_check_auth::<AccountError>(
+            &account_id,
+            &payload,
+            sign(&env, &new_key, &payload).into_val(&env),
+            &vec![&env],
+        );
+        assert_eq!(new_res, Ok(()));
+    }
+
+    fn sign_session(env: &Env, session_key: &SigningKey, payload: &BytesN<32>) -> AccountSignature {
+        let pubkey = BytesN::from_array(env, &session_key.verifying_key().to_bytes());
+        let sig = session_key.sign(&payload.to_array()).to_bytes();
+        AccountSignature::Session(pubkey, BytesN::from_array(env, &sig))
+    }
+
+    fn call_context(env: &Env, contract: &Address, fn_name: Symbol) -> Context {
+        Context::Contract(ContractContext {
+            contract: contract.clone(),
+            fn_name,
+            args: vec![env],
+        })
+    }
+
+    #[test]
+    fn test_check_auth_with_session_key() {
+        let env = Env::default();
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (account_id, auth_client) = setup(&env, &signing_key);
+
+        let data_contract = Address::generate(&env);
+        let session_key = SigningKey::from_bytes(&[11; 32]);
+        let session_pk = BytesN::from_array(&env, &session_key.verifying_key().to_bytes());
+        let expiry = env.ledger().sequence() + 100;
+        let scopes = vec![&env, SessionScope::Function(data_contract.clone(), symbol_short!("store"))];
+        auth_client.issue_session(&account_id, &session_pk, &expiry, &scopes, &1);
+
+        let payload = BytesN::from_array(&env, &[3; 32]);
+        let out_of_scope = env.try_invoke_contract_check_auth::<AccountError>(
+            &account_id,
+            &payload,
+            sign_session(&env, &session_key, &payload).into_val(&env),
+            &vec![&env, call_context(&env, &data_contract, symbol_short!("delete"))],
+        );
+        assert_eq!(out_of_scope, Err(Ok(AccountError::SessionRejected)));
+
+        let in_scope = env.try_invoke_contract_check_auth::<AccountError>(
+            &account_id,
+            &payload,
+            sign_session(&env, &session_key, &payload).into_val(&env),
+            &vec![&env, call_context(&env, &data_contract, symbol_short!("store"))],
+        );
+        assert_eq!(in_scope, Ok(()));
+        assert_eq!(auth_client.list_sessions(&account_id).get_unchecked(0).uses, 1);
+
+        // The single allowed use is spent
+        let reused = env.try_invoke_contract_check_auth::<AccountError>(
+            &account_id,
+            &payload,
+            sign_session(&env, &session_key, &payload).into_val(&env),
+            &vec![&env, call_context(&env, &data_contract, symbol_short!("store"))],
+        );
+        assert_eq!(reused, Err(Ok(AccountError::SessionRejected)));
+    }
+
+    #[test]
+    fn test_check_auth_rejects_expired_session() {
+        let env = Env::default();
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (account_id, auth_client) = setup(&env, &signing_key);
+
+        let data_contract = Address::generate(&env);
+        let session_key = SigningKey::from_bytes(&[11; 32]);
+        let session_pk = BytesN::from_array(&env, &session_key.verifying_key().to_bytes());
+        let expiry = env.ledger().sequence() + 100;
+        let scopes = vec![&env, SessionScope::Contract(data_contract.clone())];
+        auth_client.issue_session(&account_id, &session_pk, &expiry, &scopes, &10);
+
+        env.ledger().with_mut(|li| li.sequence_number = expiry + 1);
+        let payload = BytesN::from_array(&env, &[3; 32]);
+        let res = env.try_invoke_contract_check_auth::<AccountError>(
+            &account_id,
+            &payload,
+            sign_session(&env, &session_key, &payload).into_val(&env),
+            &vec![&env, call_context(&env, &data_contract, symbol_short!("store"))],
+        );
+        assert_eq!(res, Err(Ok(AccountError::SessionRejected)));
+    }
+}
@@ -1,5 +1,5 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, String, Val, Vec, symbol_short, IntoVal, contracterror};
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Vec, symbol_short, contracterror};
 
 #[contracterror]
 #[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
@@ -11,10 +11,11 @@ pub enum RegistryError {
     ContractNotSet = 4,
     UserAlreadyRegistered = 5,
     StorageError = 7, // For SDK storage errors
+    UserNotRegistered = 8,
 }
 
 // Define contract types
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 #[contracttype]
 pub enum ContractType {
     Auth = 0,
@@ -31,7 +32,7 @@ pub enum DataKey {
     DataContract,
     AuthContract,
     CommunityContract,
-    User(Address),
+    User(Address), // User -> ledger sequence at registration
 }
 
 // Define registry contract with storage of other contract addresses
@@ -93,7 +94,7 @@ impl RegistryContract {
         if env.storage().instance().has(&DataKey::User(user.clone())) {
             return Err(RegistryError::UserAlreadyRegistered);
         }
-        env.storage().instance().set(&DataKey::User(user.clone()), &true);
+        env.storage().instance().set(&DataKey::User(user.clone()), &env.ledger().sequence());
         
         // Get existing registry or create new one
         let mut registry = if let Some(registry_val) = env.storage().instance().get::<DataKey, Vec<Address>>(&DataKey::UserRegistry) {
@@ -116,6 +117,16 @@ impl RegistryContract {
         env.storage().instance().has(&DataKey::User(user))
     }
     
+    // Ledger sequence at which a user registered, so callers can enforce a minimum account age
+    pub fn get_registered_at(env: Env, user: Address) -> Result<u32, RegistryError> {
+        let registered_at = env.storage().instance().get::<DataKey, u32>(&DataKey::User(user));
+        if let Some(ledger) = registered_at {
+            Ok(ledger)
+        } else {
+            Err(RegistryError::UserNotRegistered)
+        }
+    }
+    
     pub fn get_data_contract(env: Env) -> Result<Address, RegistryError> {
         let data_contract = env.storage().instance().get::<DataKey, Address>(&DataKey::DataContract);
         if let Some(addr) = data_contract {
@@ -166,8 +177,8 @@ impl RegistryContract {
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::testutils::{Address as _, Events};
-    use soroban_sdk::{vec, Symbol, IntoVal};
+    use soroban_sdk::testutils::{Address as _, Events, Ledger};
+    use soroban_sdk::{vec, IntoVal};
 
     #[test]
     fn test_initialize() {
@@ -181,7 +192,7 @@ mod test {
         
         assert_eq!(client.try_initialize(&owner), Ok(Ok(())));
         
-        let stored_owner: Address = env.storage().instance().get(&DataKey::Owner).unwrap();
+        let stored_owner: Address = env.as_contract(&contract_id, || env.storage().instance().get(&DataKey::Owner).unwrap());
         assert_eq!(stored_owner, owner);
 
         let events = env.events().all();
@@ -190,11 +201,7 @@ mod test {
         let expected_topics = (symbol_short!("init"), symbol_short!("reg")).into_val(&env);
         let expected_data = owner.clone().into_val(&env); // Clone owner for into_val
 
-        let (event_contract_id, event_topics, event_data) = events.last().unwrap();
-        
-        assert_eq!(event_contract_id, &contract_id);
-        assert_eq!(event_topics, &expected_topics);
-        assert_eq!(event_data, &expected_data);
+        assert_eq!(events, vec![&env, (contract_id, expected_topics, expected_data)]);
     }
     
     #[test]
@@ -238,8 +245,11 @@ mod test {
         let user1 = Address::generate(&env);
         
         assert!(!client.is_registered(&user1));
+        assert_eq!(client.try_get_registered_at(&user1), Err(Ok(RegistryError::UserNotRegistered)));
+        env.ledger().with_mut(|li| li.sequence_number = 42);
         client.register_user(&user1);
         assert!(client.is_registered(&user1));
+        assert_eq!(client.get_registered_at(&user1), 42);
 
         let users_vec = client.get_users();
         assert_eq!(users_vec.len(), 1);